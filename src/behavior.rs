@@ -4,6 +4,21 @@ use rand::seq::SliceRandom;
 use rand::Rng;
 use std::ptr;
 
+// Plant growth stages (extra_data2). Plants grown from a Seed start as a Trunk, while placed Plants use the first two.
+pub const PLANT_LOOSE: i8 = 0;
+pub const PLANT_GROWABLE: i8 = 1;
+pub const PLANT_TRUNK: i8 = 2;
+pub const PLANT_BRANCH: i8 = 3;
+pub const PLANT_LEAF: i8 = 4;
+pub const PLANT_FLOWER: i8 = 5;
+/// Added to a Plant's growth stage when it wilts
+pub const PLANT_WILTED: i8 = 8;
+
+// A Virus's extra_data2 holds the virus_host_id of the particle it infected in its low bits,
+// and that particle's own extra_data2 (such as a Plant's growth stage) above them, to restore it when cured
+pub const VIRUS_HOST_ID_BITS: i8 = 3;
+pub const VIRUS_HOST_ID_MASK: i8 = (1 << VIRUS_HOST_ID_BITS) - 1;
+
 /// Returns a random available neighbor of (x, y) if any.
 /// Searches x-1 and x+1 at a y-coordinate of y + y_offset
 fn rand_available_neighbor(
@@ -34,6 +49,43 @@ fn rand_available_neighbor(
     }
 }
 
+/// Offsets to the 8 neighbors of a cell, in clockwise order
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Returns whether (x, y) is next to a particle or the edge of the sandbox, not counting (ignore_x, ignore_y)
+fn is_next_to_surface(
+    sandbox: &Sandbox,
+    x: usize,
+    y: usize,
+    ignore_x: usize,
+    ignore_y: usize,
+) -> bool {
+    for (x_offset, y_offset) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+        let x2 = x as isize + x_offset;
+        let y2 = y as isize + y_offset;
+        if !(0..(SANDBOX_WIDTH as isize)).contains(&x2)
+            || !(0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+        {
+            return true;
+        }
+        let x2 = x2 as usize;
+        let y2 = y2 as usize;
+        if (x2, y2) != (ignore_x, ignore_y) && sandbox[x2][y2].is_some() {
+            return true;
+        }
+    }
+    false
+}
+
 pub fn move_solid(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, usize) {
     // Move 1 down if able
     if y != SANDBOX_HEIGHT - 1 {
@@ -105,7 +157,7 @@ pub fn move_gas(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, usize) {
 }
 
 pub fn move_electricity(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, usize) {
-    // Try switching with an adjacent water particle in the last direction moved
+    // Try switching with an adjacent conductive particle in the last direction moved
     if sandbox[x][y].unwrap().extra_data2 != 0 {
         sandbox[x][y].as_mut().unwrap().extra_data2 -= 1;
         let offset = match sandbox[x][y].unwrap().extra_data1 {
@@ -122,7 +174,7 @@ pub fn move_electricity(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, us
             let x2 = x2 as usize;
             let y2 = y2 as usize;
             if let Some(particle) = sandbox[x2][y2] {
-                if particle.ptype == ParticleType::Water {
+                if particle.conducts_electricity() {
                     let temp = sandbox[x][y];
                     sandbox[x][y] = sandbox[x2][y2];
                     sandbox[x2][y2] = temp;
@@ -133,7 +185,7 @@ pub fn move_electricity(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, us
         return (x, y);
     }
 
-    // Else try switching with an adjacent water particle in a random direction
+    // Else try switching with an adjacent conductive particle in a random direction
     let mut offsets = [(1, 0), (-1, 0), (0, 1), (0, -1)];
     offsets.shuffle(&mut sandbox.rng);
     for offset in &offsets {
@@ -144,7 +196,7 @@ pub fn move_electricity(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, us
             let x2 = x2 as usize;
             let y2 = y2 as usize;
             if let Some(particle) = sandbox[x2][y2] {
-                if particle.ptype == ParticleType::Water {
+                if particle.conducts_electricity() {
                     let temp = sandbox[x][y];
                     temp.unwrap().extra_data1 = match offset {
                         (1, 0) => 0,
@@ -308,10 +360,125 @@ pub fn move_fire(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, usize) {
     new_position
 }
 
+pub fn move_snow(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, usize) {
+    // Fall at half speed
+    if sandbox.rng.gen_bool(0.5) {
+        return (x, y);
+    }
+
+    // Drift randomly left or right while falling if able, else move like a powder
+    if y != SANDBOX_HEIGHT - 1 && sandbox.rng.gen_bool(0.3) {
+        if let Some((new_x, new_y)) = rand_available_neighbor(sandbox, x, y, 1) {
+            sandbox[new_x][new_y] = sandbox[x][y].take();
+            return (new_x, new_y);
+        }
+    }
+    move_powder(sandbox, x, y)
+}
+
+pub fn move_cloud(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, usize) {
+    // Slowly rise if able
+    if y != 0 && sandbox.rng.gen_bool(0.05) {
+        if sandbox[x][y - 1].is_none() {
+            sandbox[x][y - 1] = sandbox[x][y].take();
+            return (x, y - 1);
+        }
+    }
+    // Else, drift randomly left or right if able
+    if sandbox.rng.gen_bool(0.2) {
+        if let Some((new_x, new_y)) = rand_available_neighbor(sandbox, x, y, 0) {
+            sandbox[new_x][new_y] = sandbox[x][y].take();
+            return (new_x, new_y);
+        }
+    }
+    (x, y)
+}
+
+pub fn move_ant(sandbox: &mut Sandbox, x: usize, y: usize) -> (usize, usize) {
+    // Fall down if there is nothing adjacent or diagonally below to walk on
+    if !is_next_to_surface(sandbox, x, y, x, y)
+        && !NEIGHBOR_OFFSETS[1..4].iter().any(|(x_offset, y_offset)| {
+            let x2 = x as isize + x_offset;
+            let y2 = y as isize + y_offset;
+            (0..(SANDBOX_WIDTH as isize)).contains(&x2)
+                && (0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+                && sandbox[x2 as usize][y2 as usize].is_some()
+        })
+    {
+        return move_solid(sandbox, x, y);
+    }
+
+    // Walk at half speed
+    if sandbox.rng.gen_bool(0.5) {
+        return (x, y);
+    }
+
+    // Find the neighboring empty cells that can be walked to, which are cells next to a surface
+    let mut candidates = [0; 8];
+    let mut candidate_count = 0;
+    for (i, (x_offset, y_offset)) in NEIGHBOR_OFFSETS.iter().enumerate() {
+        let x2 = x as isize + x_offset;
+        let y2 = y as isize + y_offset;
+        if (0..(SANDBOX_WIDTH as isize)).contains(&x2)
+            && (0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+        {
+            let x2 = x2 as usize;
+            let y2 = y2 as usize;
+            if sandbox[x2][y2].is_none() && is_next_to_surface(sandbox, x2, y2, x, y) {
+                candidates[candidate_count] = i;
+                candidate_count += 1;
+            }
+        }
+    }
+    let candidates = &candidates[..candidate_count];
+    if candidates.is_empty() {
+        return (x, y);
+    }
+
+    // Follow the strongest pheromone trail, else keep walking in the same direction (extra_data1 % 8),
+    // else turn randomly without walking backwards if able
+    let direction = (sandbox[x][y].unwrap().extra_data1 % 8) as usize;
+    let backwards = (direction + 4) % 8;
+    let pheromone = |i: usize| {
+        let (x_offset, y_offset) = NEIGHBOR_OFFSETS[i];
+        sandbox.pheromones[(x as isize + x_offset) as usize][(y as isize + y_offset) as usize]
+    };
+    let strongest_trail = candidates
+        .iter()
+        .copied()
+        .filter(|i| *i != backwards)
+        .max_by_key(|i| pheromone(*i))
+        .filter(|i| pheromone(*i) > 0);
+    let new_direction = match strongest_trail {
+        Some(i) if sandbox.rng.gen_bool(0.7) => i,
+        _ => {
+            if candidates.contains(&direction) && sandbox.rng.gen_bool(0.8) {
+                direction
+            } else {
+                let forwards = candidates
+                    .iter()
+                    .copied()
+                    .filter(|i| *i != backwards)
+                    .collect::<Vec<usize>>();
+                *forwards.choose(&mut sandbox.rng).unwrap_or(&candidates[0])
+            }
+        }
+    };
+
+    // Move and remember the direction, keeping the carrying Sand flag (extra_data1 >= 8)
+    let (x_offset, y_offset) = NEIGHBOR_OFFSETS[new_direction];
+    let x2 = (x as isize + x_offset) as usize;
+    let y2 = (y as isize + y_offset) as usize;
+    let ant = sandbox[x][y].as_mut().unwrap();
+    ant.extra_data1 = (ant.extra_data1 / 8) * 8 + new_direction as i8;
+    sandbox[x2][y2] = sandbox[x][y].take();
+    (x2, y2)
+}
+
 pub fn update_sand(sandbox: &mut Sandbox, x: usize, y: usize) {
-    // When wet and temperature >= 30, dry out
-    if sandbox[x][y].unwrap().extra_data1 == 1 && sandbox[x][y].unwrap().temperature >= 30 {
-        sandbox[x][y].as_mut().unwrap().extra_data1 = 0;
+    // When wet or Mud and temperature >= 30, dry out one level
+    if sandbox[x][y].unwrap().extra_data1 > 0 && sandbox[x][y].unwrap().temperature >= 30 {
+        sandbox[x][y].as_mut().unwrap().extra_data1 -= 1;
     }
 
     // When temperature >= 120, turn into Glass
@@ -341,10 +508,23 @@ pub fn update_water(sandbox: &mut Sandbox, x: usize, y: usize) {
                     return;
                 }
             }
-            _ => return,
+            _ => break,
         }
         y2 += 1;
     }
+
+    // Else if directly above wet Sand, chance to delete this particle and saturate the Sand into Mud
+    if y != SANDBOX_HEIGHT - 1 {
+        if let Some(particle) = sandbox[x][y + 1] {
+            if particle.ptype == ParticleType::Sand
+                && particle.extra_data1 == 1
+                && sandbox.rng.gen_bool(0.05)
+            {
+                sandbox[x][y] = None;
+                sandbox[x][y + 1].as_mut().unwrap().extra_data1 = 2;
+            }
+        }
+    }
 }
 
 pub fn update_acid(sandbox: &mut Sandbox, x: usize, y: usize) {
@@ -517,21 +697,27 @@ pub fn update_plant(sandbox: &mut Sandbox, x: usize, y: usize) {
         return;
     }
 
-    // If above wet Sand or another Plant that's growable, mark as growable (extra_data2 = 1)
+    // Plants grown from a Seed have their own growth rules
+    if sandbox[x][y].unwrap().extra_data2 >= PLANT_TRUNK {
+        update_grown_plant(sandbox, x, y);
+        return;
+    }
+
+    // If above wet Sand, Mud, or another Plant that's growable, mark as growable
     if y != SANDBOX_HEIGHT - 1 {
         if let Some(particle) = sandbox[x][y + 1] {
-            if particle.ptype == ParticleType::Sand && particle.extra_data1 == 1 {
-                sandbox[x][y].as_mut().unwrap().extra_data2 = 1;
+            if particle.ptype == ParticleType::Sand && particle.extra_data1 >= 1 {
+                sandbox[x][y].as_mut().unwrap().extra_data2 = PLANT_GROWABLE;
             }
-            if particle.ptype == ParticleType::Plant && particle.extra_data2 == 1 {
-                sandbox[x][y].as_mut().unwrap().extra_data2 = 1;
+            if particle.ptype == ParticleType::Plant && particle.extra_data2 == PLANT_GROWABLE {
+                sandbox[x][y].as_mut().unwrap().extra_data2 = PLANT_GROWABLE;
             }
         }
     }
 
     // If growable and growing_time_left (extra_data_1) > 0, create another Plant nearby with 1 less growing_time_left
     if y != 0 {
-        if sandbox[x][y].unwrap().extra_data2 == 1 {
+        if sandbox[x][y].unwrap().extra_data2 == PLANT_GROWABLE {
             let extra_data1 = sandbox[x][y].unwrap().extra_data1;
             if extra_data1 > 0 {
                 let x_offset = sandbox.rng.gen_range(-1..2);
@@ -544,7 +730,7 @@ pub fn update_plant(sandbox: &mut Sandbox, x: usize, y: usize) {
                     if sandbox[x as usize][y as usize].is_none() {
                         let mut particle = Particle::new(ParticleType::Plant, &mut sandbox.rng);
                         particle.extra_data1 = extra_data1 - 1;
-                        particle.extra_data2 = 1;
+                        particle.extra_data2 = PLANT_GROWABLE;
                         sandbox[x as usize][y as usize] = Some(particle);
                     }
                 }
@@ -553,6 +739,187 @@ pub fn update_plant(sandbox: &mut Sandbox, x: usize, y: usize) {
     }
 }
 
+/// Update a Plant grown from a Seed. The tips of the plant (growing_time_left (extra_data_1) > 0) grow a trunk upwards
+/// while drinking water from the soil, which sprouts branches, leaves, and finally a flower.
+fn update_grown_plant(sandbox: &mut Sandbox, x: usize, y: usize) {
+    let plant = sandbox[x][y].unwrap();
+
+    // When wilted, chance to spread wilting to the rest of the plant
+    if plant.extra_data2 >= PLANT_WILTED {
+        for (x_offset, y_offset) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let x2 = x as isize + x_offset;
+            let y2 = y as isize + y_offset;
+            if (0..(SANDBOX_WIDTH as isize)).contains(&x2)
+                && (0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+            {
+                if let Some(particle) = sandbox[x2 as usize][y2 as usize] {
+                    if particle.ptype == ParticleType::Plant
+                        && (PLANT_TRUNK..PLANT_WILTED).contains(&particle.extra_data2)
+                        && sandbox.rng.gen_bool(0.1)
+                    {
+                        sandbox[x2 as usize][y2 as usize]
+                            .as_mut()
+                            .unwrap()
+                            .extra_data2 += PLANT_WILTED;
+                    }
+                }
+            }
+        }
+        return;
+    }
+
+    // When temperature < -40, wilt
+    if plant.temperature < -40 {
+        sandbox[x][y].as_mut().unwrap().extra_data2 += PLANT_WILTED;
+        return;
+    }
+
+    // When a root (a Trunk above Sand), chance to drink water from the soil, and wilt if it's dry
+    if plant.extra_data2 == PLANT_TRUNK
+        && y != SANDBOX_HEIGHT - 1
+        && sandbox[x][y + 1].map(|p| p.ptype) == Some(ParticleType::Sand)
+        && sandbox.rng.gen_bool(0.002)
+    {
+        match find_soil_water(sandbox, x, y + 1) {
+            Some(soil_y) => sandbox[x][soil_y].as_mut().unwrap().extra_data1 -= 1,
+            None => {
+                sandbox[x][y].as_mut().unwrap().extra_data2 += PLANT_WILTED;
+                return;
+            }
+        }
+    }
+
+    // Chance for the tips of the plant to grow
+    let growing_time_left = plant.extra_data1;
+    if growing_time_left == 0 || !sandbox.rng.gen_bool(0.05) {
+        return;
+    }
+    match plant.extra_data2 {
+        PLANT_TRUNK => {
+            // Grow upwards if there's water in the soil below the roots
+            if y == 0 || sandbox[x][y - 1].is_some() {
+                sandbox[x][y].as_mut().unwrap().extra_data1 = 0;
+                return;
+            }
+            let mut root_y = y;
+            while root_y < SANDBOX_HEIGHT
+                && sandbox[x][root_y].map(|p| p.ptype) == Some(ParticleType::Plant)
+            {
+                root_y += 1;
+            }
+            let soil_y = match find_soil_water(sandbox, x, root_y) {
+                Some(soil_y) => soil_y,
+                None => return,
+            };
+            if sandbox.rng.gen_bool(0.3) {
+                sandbox[x][soil_y].as_mut().unwrap().extra_data1 -= 1;
+            }
+
+            sandbox[x][y].as_mut().unwrap().extra_data1 = 0;
+            if growing_time_left == 1 {
+                sandbox[x][y - 1] = Some(grown_plant(sandbox, PLANT_FLOWER, 0));
+            } else {
+                sandbox[x][y - 1] = Some(grown_plant(sandbox, PLANT_TRUNK, growing_time_left - 1));
+            }
+
+            // Chance to sprout a branch to the side
+            if growing_time_left > 4 && sandbox.rng.gen_bool(0.35) {
+                let x2 = [x as isize - 1, x as isize + 1][sandbox.rng.gen_range(0..2)];
+                if (0..(SANDBOX_WIDTH as isize)).contains(&x2) && sandbox[x2 as usize][y].is_none()
+                {
+                    let growing_time_left = (growing_time_left / 2).min(10);
+                    sandbox[x2 as usize][y] =
+                        Some(grown_plant(sandbox, PLANT_BRANCH, growing_time_left));
+                }
+            }
+        }
+        PLANT_BRANCH => {
+            // Grow sideways and upwards, away from the rest of the plant
+            let is_plant = |x: isize, y: isize| {
+                (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                    && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                    && sandbox[x as usize][y as usize].map(|p| p.ptype) == Some(ParticleType::Plant)
+            };
+            let (xi, yi) = (x as isize, y as isize);
+            let direction = if is_plant(xi - 1, yi) || is_plant(xi - 1, yi + 1) {
+                1
+            } else if is_plant(xi + 1, yi) || is_plant(xi + 1, yi + 1) {
+                -1
+            } else {
+                [-1, 1][sandbox.rng.gen_range(0..2)]
+            };
+            let x2 = xi + direction;
+            let y2 = if sandbox.rng.gen_bool(0.5) {
+                yi - 1
+            } else {
+                yi
+            };
+
+            sandbox[x][y].as_mut().unwrap().extra_data1 = 0;
+            if !(0..(SANDBOX_WIDTH as isize)).contains(&x2)
+                || !(0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+                || sandbox[x2 as usize][y2 as usize].is_some()
+            {
+                return;
+            }
+            let (x2, y2) = (x2 as usize, y2 as usize);
+            if growing_time_left == 1 {
+                // Finish with a cluster of leaves, and sometimes a flower
+                let stage = if sandbox.rng.gen_bool(0.3) {
+                    PLANT_FLOWER
+                } else {
+                    PLANT_LEAF
+                };
+                sandbox[x2][y2] = Some(grown_plant(sandbox, stage, 0));
+                for (x_offset, y_offset) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+                    let x3 = x2 as isize + x_offset;
+                    let y3 = y2 as isize + y_offset;
+                    if (0..(SANDBOX_WIDTH as isize)).contains(&x3)
+                        && (0..(SANDBOX_HEIGHT as isize)).contains(&y3)
+                        && sandbox[x3 as usize][y3 as usize].is_none()
+                    {
+                        sandbox[x3 as usize][y3 as usize] =
+                            Some(grown_plant(sandbox, PLANT_LEAF, 0));
+                    }
+                }
+            } else {
+                sandbox[x2][y2] = Some(grown_plant(sandbox, PLANT_BRANCH, growing_time_left - 1));
+
+                // Chance to grow a leaf on top of the branch
+                if y != 0 && sandbox[x][y - 1].is_none() && sandbox.rng.gen_bool(0.5) {
+                    sandbox[x][y - 1] = Some(grown_plant(sandbox, PLANT_LEAF, 0));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Create a Plant particle at the given growth stage
+fn grown_plant(sandbox: &mut Sandbox, stage: i8, growing_time_left: i8) -> Particle {
+    let mut particle = Particle::new(ParticleType::Plant, &mut sandbox.rng);
+    particle.extra_data1 = growing_time_left;
+    particle.extra_data2 = stage;
+    particle
+}
+
+/// Returns the y-coordinate of the first wet Sand or Mud at or below (x, y), searching through Sand only
+fn find_soil_water(sandbox: &Sandbox, x: usize, y: usize) -> Option<usize> {
+    let mut y2 = y;
+    while y2 < SANDBOX_HEIGHT {
+        match sandbox[x][y2] {
+            Some(particle) if particle.ptype == ParticleType::Sand => {
+                if particle.extra_data1 > 0 {
+                    return Some(y2);
+                }
+            }
+            _ => return None,
+        }
+        y2 += 1;
+    }
+    None
+}
+
 // Check if temperature >= 0, and if so, wait 1/3rd of a second, and then delete itself and freeze around it
 pub fn update_cryotheum(sandbox: &mut Sandbox, x: usize, y: usize) {
     if sandbox[x][y].unwrap().temperature >= 0 && sandbox[x][y].unwrap().extra_data1 == 0 {
@@ -848,3 +1215,259 @@ pub fn update_glitch(sandbox: &mut Sandbox, x: usize, y: usize) {
         }
     }
 }
+
+pub fn update_salt(sandbox: &mut Sandbox, x: usize, y: usize) {
+    // Chance to dissolve into an adjacent Water particle, turning it into Saltwater
+    let mut offsets = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+    offsets.shuffle(&mut sandbox.rng);
+    for offset in &offsets {
+        let (x2, y2) = (x as isize + offset.0, y as isize + offset.1);
+        if (0..(SANDBOX_WIDTH as isize)).contains(&x2)
+            && (0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+        {
+            let x2 = x2 as usize;
+            let y2 = y2 as usize;
+            if let Some(particle) = sandbox[x2][y2] {
+                if particle.ptype == ParticleType::Water && sandbox.rng.gen_bool(0.05) {
+                    sandbox[x][y] = None;
+                    sandbox[x2][y2].as_mut().unwrap().ptype = ParticleType::SaltWater;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+pub fn update_salt_water(sandbox: &mut Sandbox, x: usize, y: usize) {
+    // When boiling with room above, turn into Salt and release the water as Steam above
+    let temperature = sandbox[x][y].unwrap().temperature;
+    if temperature >= 100 && y != 0 && sandbox[x][y - 1].is_none() {
+        let t = (temperature as f64 / 150.0).clamp(0.0, 1.0);
+        let chance = (1.0 - t) * 0.3 + t * 0.7;
+        if sandbox.rng.gen_bool(chance) {
+            sandbox[x][y].as_mut().unwrap().ptype = ParticleType::Salt;
+            let mut particle = Particle::new(ParticleType::Steam, &mut sandbox.rng);
+            particle.temperature = temperature;
+            sandbox[x][y - 1] = Some(particle);
+        }
+    }
+}
+
+pub fn update_snow(sandbox: &mut Sandbox, x: usize, y: usize) {
+    // When temperature >= -40, melt into Water
+    if sandbox[x][y].unwrap().temperature >= -40 {
+        sandbox[x][y].as_mut().unwrap().ptype = ParticleType::Water;
+        return;
+    }
+
+    // When enough particles are stacked above, chance to pack into Ice (frozen Water)
+    let mut count = 1;
+    while count <= y {
+        match sandbox[x][y - count] {
+            Some(_) => count += 1,
+            None => break,
+        }
+    }
+    count -= 1;
+    if count > 15 && sandbox.rng.gen_bool(0.05) {
+        let particle = sandbox[x][y].as_mut().unwrap();
+        particle.ptype = ParticleType::Water;
+        particle.temperature = particle.temperature.min(-90);
+    }
+}
+
+pub fn update_cloud(sandbox: &mut Sandbox, x: usize, y: usize) {
+    // Count down until the next drop (extra_data1)
+    if sandbox[x][y].unwrap().extra_data1 > 0 {
+        sandbox[x][y].as_mut().unwrap().extra_data1 -= 1;
+        return;
+    }
+
+    // Drop Water below, or Snow when cold, and dissipate after running out of drops (extra_data2)
+    if y != SANDBOX_HEIGHT - 1 && sandbox[x][y + 1].is_none() {
+        let ptype = if sandbox[x][y].unwrap().temperature < -20 {
+            ParticleType::Snow
+        } else {
+            ParticleType::Water
+        };
+        sandbox[x][y + 1] = Some(Particle::new(ptype, &mut sandbox.rng));
+
+        sandbox[x][y].as_mut().unwrap().extra_data2 -= 1;
+        if sandbox[x][y].unwrap().extra_data2 <= 0 {
+            sandbox[x][y] = None;
+            return;
+        }
+    }
+    sandbox[x][y].as_mut().unwrap().extra_data1 = sandbox.rng.gen_range(30..90);
+}
+
+pub fn update_virus(sandbox: &mut Sandbox, x: usize, y: usize) {
+    // When temperature >= 40, the Virus dies
+    if sandbox[x][y].unwrap().temperature >= 40 {
+        cure_virus(sandbox, x, y);
+        return;
+    }
+
+    // If infecting a particle, count down the incubation period (extra_data1), and then kill the infected particle
+    if sandbox[x][y].unwrap().extra_data2 != 0 {
+        let particle = sandbox[x][y].as_mut().unwrap();
+        particle.extra_data1 -= 1;
+        if particle.extra_data1 <= 0 {
+            particle.extra_data1 = 0;
+            particle.extra_data2 = 0;
+        }
+    }
+
+    // Chance to infect adjacent particles
+    for (x_offset, y_offset) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+        let x2 = x as isize + x_offset;
+        let y2 = y as isize + y_offset;
+        if (0..(SANDBOX_WIDTH as isize)).contains(&x2)
+            && (0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+        {
+            let x2 = x2 as usize;
+            let y2 = y2 as usize;
+            if let Some(particle) = sandbox[x2][y2] {
+                let host_id = particle.ptype.virus_host_id();
+                let infection_chance = sandbox.virus_infection_chance.clamp(0.0, 1.0);
+                if host_id != 0 && sandbox.rng.gen_bool(infection_chance) {
+                    let particle = sandbox[x2][y2].as_mut().unwrap();
+                    let host_data = particle.extra_data2.clamp(0, 15);
+                    particle.ptype = ParticleType::Virus;
+                    particle.extra_data1 = 120;
+                    particle.extra_data2 = host_id | (host_data << VIRUS_HOST_ID_BITS);
+                }
+            }
+        }
+    }
+}
+
+pub fn update_antidote(sandbox: &mut Sandbox, x: usize, y: usize) {
+    // Cure an adjacent Virus particle, using up this particle
+    for (x_offset, y_offset) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+        let x2 = x as isize + x_offset;
+        let y2 = y as isize + y_offset;
+        if (0..(SANDBOX_WIDTH as isize)).contains(&x2)
+            && (0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+        {
+            let x2 = x2 as usize;
+            let y2 = y2 as usize;
+            if sandbox[x2][y2].map(|p| p.ptype) == Some(ParticleType::Virus) {
+                cure_virus(sandbox, x2, y2);
+                sandbox[x][y] = None;
+                return;
+            }
+        }
+    }
+}
+
+/// Deletes the Virus particle at (x, y), restoring the particle it was infecting if any
+fn cure_virus(sandbox: &mut Sandbox, x: usize, y: usize) {
+    let virus = sandbox[x][y].unwrap();
+    match ParticleType::from_virus_host_id(virus.extra_data2) {
+        Some(host) => {
+            let mut particle = Particle::new(host, &mut sandbox.rng);
+            particle.temperature = virus.temperature;
+            particle.color_offset = virus.color_offset;
+            particle.extra_data2 = virus.extra_data2 >> VIRUS_HOST_ID_BITS;
+            // Parts of a grown Plant have already finished growing
+            if host == ParticleType::Plant && particle.extra_data2 >= PLANT_TRUNK {
+                particle.extra_data1 = 0;
+            }
+            sandbox[x][y] = Some(particle);
+        }
+        None => sandbox[x][y] = None,
+    }
+}
+
+pub fn update_ant(sandbox: &mut Sandbox, x: usize, y: usize) {
+    let carrying_sand = sandbox[x][y].unwrap().extra_data1 >= 8;
+
+    // Count how long this particle has been underwater (extra_data2)
+    let underwater = y != 0
+        && match sandbox[x][y - 1] {
+            Some(particle) => {
+                particle.ptype == ParticleType::Water || particle.ptype == ParticleType::SaltWater
+            }
+            None => false,
+        };
+    let ant = sandbox[x][y].as_mut().unwrap();
+    if underwater {
+        ant.extra_data2 = ant.extra_data2.saturating_add(1);
+    } else {
+        ant.extra_data2 = 0;
+    }
+
+    // When temperature less than -50, or greater than 50, or underwater for 2 seconds, die and drop any carried Sand
+    if ant.temperature < -50 || ant.temperature > 50 || ant.extra_data2 > 120 {
+        sandbox[x][y] = if carrying_sand {
+            Some(Particle::new(ParticleType::Sand, &mut sandbox.rng))
+        } else {
+            None
+        };
+        return;
+    }
+
+    // Leave a pheromone trail, stronger when carrying Sand
+    sandbox.pheromones[x][y] = if carrying_sand { 255 } else { 160 };
+
+    let in_the_open = (0..y).all(|y2| sandbox[x][y2].is_none());
+    let in_a_tunnel = !in_the_open
+        || ((x == 0 || sandbox[x - 1][y].is_some())
+            && (x == SANDBOX_WIDTH - 1 || sandbox[x + 1][y].is_some()));
+    if !carrying_sand {
+        // Chance to dig into adjacent Sand, picking it up, preferring to dig downwards and while already in a tunnel
+        let dig_chance = if in_a_tunnel { 0.2 } else { 0.02 };
+        if sandbox.rng.gen_bool(dig_chance) {
+            let mut offsets = [(0, 1), (1, 0), (-1, 0), (0, -1)];
+            if sandbox.rng.gen_bool(0.5) {
+                offsets.shuffle(&mut sandbox.rng);
+            }
+            for offset in &offsets {
+                let (x2, y2) = (x as isize + offset.0, y as isize + offset.1);
+                if (0..(SANDBOX_WIDTH as isize)).contains(&x2)
+                    && (0..(SANDBOX_HEIGHT as isize)).contains(&y2)
+                {
+                    let x2 = x2 as usize;
+                    let y2 = y2 as usize;
+                    if sandbox[x2][y2].map(|p| p.ptype) == Some(ParticleType::Sand) {
+                        sandbox[x2][y2] = None;
+                        sandbox[x][y].as_mut().unwrap().extra_data1 += 8;
+                        return;
+                    }
+                }
+            }
+        }
+    } else if y != 0 && x != 0 && x != SANDBOX_WIDTH - 1 && sandbox.rng.gen_bool(0.1) {
+        // When carrying Sand and out of the tunnel, chance to drop it above
+        if !in_a_tunnel {
+            sandbox[x][y - 1] = Some(Particle::new(ParticleType::Sand, &mut sandbox.rng));
+            sandbox[x][y].as_mut().unwrap().extra_data1 -= 8;
+        }
+    }
+}
+
+pub fn update_seed(sandbox: &mut Sandbox, x: usize, y: usize) {
+    // If temperature > 100, turn into Fire
+    if sandbox[x][y].unwrap().temperature > 100 {
+        sandbox[x][y].as_mut().unwrap().ptype = ParticleType::Fire;
+        return;
+    }
+
+    // If above wet Sand or Mud, chance to drink from it and germinate into a Plant
+    if y != SANDBOX_HEIGHT - 1 {
+        if let Some(particle) = sandbox[x][y + 1] {
+            if particle.ptype == ParticleType::Sand
+                && particle.extra_data1 > 0
+                && sandbox.rng.gen_bool(0.01)
+            {
+                sandbox[x][y + 1].as_mut().unwrap().extra_data1 -= 1;
+                let temperature = sandbox[x][y].unwrap().temperature;
+                let growing_time_left = sandbox.rng.gen_range(20..45);
+                let mut particle = grown_plant(sandbox, PLANT_TRUNK, growing_time_left);
+                particle.temperature = temperature;
+                sandbox[x][y] = Some(particle);
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::particle::{Particle, ParticleType};
+use crate::sandbox::{particle_color, Sandbox, SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use std::io::{self, ErrorKind, Read, Write};
+
+pub const MAX_BRUSH_SIZE: u8 = 50;
+pub const MIN_BRUSH_TEMPERATURE: i16 = -300;
+pub const MAX_BRUSH_TEMPERATURE: i16 = 300;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum BrushShape {
+    Square,
+    Circle,
+    Spray,
+    Stamp,
+}
+
+/// A copy of a region of the sandbox, which can be placed back down with the Stamp brush
+#[derive(Clone)]
+pub struct Stamp {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<Option<Particle>>,
+}
+
+impl Stamp {
+    /// Copy the region of the sandbox starting at (x, y), clipped to the sandbox bounds
+    pub fn copy_from(sandbox: &Sandbox, x: usize, y: usize, width: usize, height: usize) -> Self {
+        let width = width.min(SANDBOX_WIDTH - x);
+        let height = height.min(SANDBOX_HEIGHT - y);
+        let mut cells = Vec::with_capacity(width * height);
+        for y in y..(y + height) {
+            for x in x..(x + width) {
+                cells.push(sandbox[x][y]);
+            }
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Particle> {
+        self.cells[y * self.width + x]
+    }
+
+    /// Write the stamp's size, followed by the full state of each cell
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(&(self.width as u16).to_le_bytes())?;
+        writer.write_all(&(self.height as u16).to_le_bytes())?;
+        for cell in &self.cells {
+            match cell {
+                Some(particle) => {
+                    let ptype_i = ParticleType::ALL
+                        .iter()
+                        .position(|ptype| *ptype == particle.ptype)
+                        .unwrap();
+                    let temperature = particle.temperature.to_le_bytes();
+                    writer.write_all(&[
+                        ptype_i as u8 + 1,
+                        temperature[0],
+                        temperature[1],
+                        particle.extra_data1 as u8,
+                        particle.extra_data2 as u8,
+                        particle.color_offset as u8,
+                        particle.last_update,
+                    ])?;
+                }
+                None => writer.write_all(&[0])?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a stamp written by write_to()
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut size = [0; 4];
+        reader.read_exact(&mut size)?;
+        let width = u16::from_le_bytes([size[0], size[1]]) as usize;
+        let height = u16::from_le_bytes([size[2], size[3]]) as usize;
+        if width == 0 || height == 0 || width > SANDBOX_WIDTH || height > SANDBOX_HEIGHT {
+            return Err(io::Error::new(ErrorKind::InvalidData, "invalid stamp size"));
+        }
+
+        let mut cells = Vec::with_capacity(width * height);
+        for _ in 0..(width * height) {
+            let mut ptype_i = [0];
+            reader.read_exact(&mut ptype_i)?;
+            if ptype_i[0] == 0 {
+                cells.push(None);
+                continue;
+            }
+            let ptype = *ParticleType::ALL
+                .get(ptype_i[0] as usize - 1)
+                .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "invalid particle type"))?;
+
+            let mut data = [0; 6];
+            reader.read_exact(&mut data)?;
+            cells.push(Some(Particle {
+                ptype,
+                temperature: i16::from_le_bytes([data[0], data[1]]),
+                extra_data1: data[2] as i8,
+                extra_data2: data[3] as i8,
+                color_offset: data[4] as i8,
+                last_update: data[5],
+            }));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Render the stamp to an RGBA image, with a dark background behind empty cells
+    pub fn render(&self) -> Vec<u8> {
+        let mut image = Vec::with_capacity(self.cells.len() * 4);
+        for cell in &self.cells {
+            let color = cell
+                .map(|particle| particle_color(&particle, 0))
+                .unwrap_or((30, 30, 30));
+            image.extend_from_slice(&[color.0, color.1, color.2, 255]);
+        }
+        image
+    }
+
+    pub fn flip_horizontally(&mut self) {
+        for row in self.cells.chunks_mut(self.width) {
+            row.reverse();
+        }
+    }
+
+    pub fn flip_vertically(&mut self) {
+        for y in 0..(self.height / 2) {
+            for x in 0..self.width {
+                self.cells
+                    .swap(y * self.width + x, (self.height - 1 - y) * self.width + x);
+            }
+        }
+    }
+
+    /// Rotate the stamp 90 degrees clockwise
+    pub fn rotate(&mut self) {
+        let mut cells = vec![None; self.cells.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                cells[x * self.height + (self.height - 1 - y)] = self.get(x, y);
+            }
+        }
+        self.cells = cells;
+        std::mem::swap(&mut self.width, &mut self.height);
+    }
+
+    /// Returns the offset from the cursor to the top-left corner of the stamp, so that it's centered on the cursor
+    pub fn origin_offset(&self) -> (isize, isize) {
+        (-(self.width as isize / 2), -(self.height as isize / 2))
+    }
+}
+
+/// Returns the offsets from the cursor to each cell covered by a brush, centered on the cursor.
+/// Spray brushes cover the same area as Circle brushes. Stamp brushes are sized by their Stamp, so use Square here.
+pub fn brush_offsets(shape: BrushShape, size: u8) -> Vec<(isize, isize)> {
+    let size = size as isize;
+    let start = -size / 2;
+    let center = (2 * start + size - 1) as f64 / 2.0;
+    let radius = size as f64 / 2.0;
+
+    let mut offsets = Vec::with_capacity((size * size) as usize);
+    for y_offset in start..(start + size) {
+        for x_offset in start..(start + size) {
+            let is_covered = match shape {
+                BrushShape::Square | BrushShape::Stamp => true,
+                BrushShape::Circle | BrushShape::Spray => {
+                    let dx = x_offset as f64 - center;
+                    let dy = y_offset as f64 - center;
+                    dx * dx + dy * dy <= radius * (radius - 0.5)
+                }
+            };
+            if is_covered {
+                offsets.push((x_offset, y_offset));
+            }
+        }
+    }
+    offsets
+}
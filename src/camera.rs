@@ -0,0 +1,88 @@
+use crate::sandbox::{SANDBOX_HEIGHT, SANDBOX_WIDTH};
+
+pub const MAX_ZOOM: usize = 8;
+
+/// Shows part of the sandbox magnified, by scaling up the rendered frame on the CPU before it's drawn.
+/// Zoom levels are whole numbers, so that every visible cell covers the same number of pixels.
+pub struct Camera {
+    zoom: usize,
+    /// The top left visible cell
+    x: usize,
+    y: usize,
+    buffer: Vec<u8>,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self {
+            zoom: 1,
+            x: 0,
+            y: 0,
+            buffer: vec![0; SANDBOX_WIDTH * SANDBOX_HEIGHT * 4],
+        }
+    }
+
+    pub fn zoom(&self) -> usize {
+        self.zoom
+    }
+
+    /// Returns the top left visible cell, and the number of visible cells in each direction
+    pub fn visible_region(&self) -> (usize, usize, usize, usize) {
+        let (width, height) = visible_size(self.zoom);
+        (self.x, self.y, width, height)
+    }
+
+    /// Returns the cell shown at a pixel of the frame
+    pub fn frame_to_grid(&self, (frame_x, frame_y): (usize, usize)) -> (usize, usize) {
+        (
+            (self.x + frame_x / self.zoom).min(SANDBOX_WIDTH - 1),
+            (self.y + frame_y / self.zoom).min(SANDBOX_HEIGHT - 1),
+        )
+    }
+
+    /// Change the zoom level, keeping the cell at a pixel of the frame in place
+    pub fn zoom_at(&mut self, frame_position: (usize, usize), zoom: usize) {
+        let grid_position = self.frame_to_grid(frame_position);
+        self.zoom = zoom.clamp(1, MAX_ZOOM);
+        self.move_cell_to(grid_position, frame_position);
+    }
+
+    /// Scroll so that a cell is shown at a pixel of the frame, as close as the edges of the sandbox allow
+    pub fn move_cell_to(&mut self, (x, y): (usize, usize), (frame_x, frame_y): (usize, usize)) {
+        let (width, height) = visible_size(self.zoom);
+        self.x = x
+            .saturating_sub(frame_x / self.zoom)
+            .min(SANDBOX_WIDTH - width);
+        self.y = y
+            .saturating_sub(frame_y / self.zoom)
+            .min(SANDBOX_HEIGHT - height);
+    }
+
+    /// Scale the visible region of the frame up to fill the whole frame
+    pub fn apply(&mut self, frame: &mut [u8]) {
+        if self.zoom == 1 {
+            return;
+        }
+
+        self.buffer.copy_from_slice(frame);
+        let mut frame_i = 0;
+        for frame_y in 0..SANDBOX_HEIGHT {
+            let y = self.y + frame_y / self.zoom;
+            for frame_x in 0..SANDBOX_WIDTH {
+                let x = self.x + frame_x / self.zoom;
+                let buffer_i = (y * SANDBOX_WIDTH + x) * 4;
+                frame[frame_i..(frame_i + 4)]
+                    .copy_from_slice(&self.buffer[buffer_i..(buffer_i + 4)]);
+                frame_i += 4;
+            }
+        }
+    }
+}
+
+/// The number of cells visible in each direction at a zoom level, including partially visible ones
+fn visible_size(zoom: usize) -> (usize, usize) {
+    (
+        (SANDBOX_WIDTH + zoom - 1) / zoom,
+        (SANDBOX_HEIGHT + zoom - 1) / zoom,
+    )
+}
@@ -1,12 +1,25 @@
+use crate::brush::{
+    brush_offsets, BrushShape, Stamp, MAX_BRUSH_TEMPERATURE, MIN_BRUSH_TEMPERATURE,
+};
+use crate::camera::Camera;
+use crate::heap_array::create_cells_array;
 use crate::particle::{Particle, ParticleType};
 use crate::post_process::PostProcess;
-use crate::sandbox::{Sandbox, SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use crate::recording::{Recording, RecordingFormat};
+use crate::sandbox::{particle_color, Sandbox, ViewMode, SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use crate::screenshot::save_screenshot;
+use crate::settings::Settings;
+use crate::symmetry::{Symmetry, SymmetryMode};
+use crate::tool::{flood_fill_points, line_points, shape_points, Tool};
 use pixels::Pixels;
+use rand::Rng;
+use std::collections::HashSet;
 use std::time::{Duration, Instant};
 use winit::dpi::PhysicalPosition;
 use winit::window::Window;
 
 const TARGET_TIME_PER_UPDATE: Duration = Duration::from_nanos(16666670);
+const MAX_UNDO_STEPS: usize = 16;
 
 pub struct Game {
     pub sandbox: Sandbox,
@@ -18,13 +31,51 @@ pub struct Game {
 
     // Particle placement info
     pub selected_particle: Option<ParticleType>,
+    pub tool: Tool,
     pub brush_size: u8,
+    pub brush_shape: BrushShape,
+    pub spray_density: f32,
+    pub brush_filter: Option<ParticleType>,
+    pub temperature_rate: i16,
+    pub target_temperature: i16,
+    pub wind_strength: f64,
+    pub symmetry: Symmetry,
+    pub stamp: Option<Stamp>,
     pub x_axis_locked: Option<f64>,
     pub y_axis_locked: Option<f64>,
     pub should_place_particles: bool,
     pub cursor_position: PhysicalPosition<f64>,
+    pub cursor_grid_position: (usize, usize),
+    /// The cell the brush is over, which stays on the locked axis while an axis lock is held
+    pub brush_grid_position: (usize, usize),
     pub previous_cursor_position: PhysicalPosition<f64>,
     pub particle_placement_queue: Vec<(PhysicalPosition<f64>, PhysicalPosition<f64>)>,
+    pub shape_start: Option<PhysicalPosition<f64>>,
+    pub should_finish_shape: bool,
+    pub shape_preview: Vec<(usize, usize)>,
+    pub should_fill: bool,
+    pub selection: Option<(usize, usize, usize, usize)>,
+    /// Whether the next click places the stamp once, replacing everything under it
+    pub is_pasting: bool,
+    pub should_paste: bool,
+
+    pub undo_history: Vec<Box<[[Option<Particle>; SANDBOX_HEIGHT]; SANDBOX_WIDTH]>>,
+    pub autosave_world: bool,
+    pub view_mode: ViewMode,
+    pub camera: Camera,
+    pub is_panning: bool,
+    pub pan_anchor: Option<(usize, usize)>,
+    pub queued_zoom: f64,
+    pub screenshot_scale: u8,
+    pub should_take_screenshot: bool,
+    /// Where the last screenshot was saved, or why it failed, and when
+    pub screenshot_status: Option<(String, Instant)>,
+    pub recording: Option<Recording>,
+    pub recording_format: RecordingFormat,
+    pub recording_frame_skip: u8,
+    pub recording_scale: u8,
+    /// Where the last recording was saved, or why it failed
+    pub recording_status: Option<String>,
 
     pub last_window_resize: Option<Instant>,
 }
@@ -39,28 +90,121 @@ impl Game {
             should_update_once: false,
 
             selected_particle: Some(ParticleType::Sand),
+            tool: Tool::Freehand,
             brush_size: 3,
+            brush_shape: BrushShape::Square,
+            spray_density: 0.1,
+            brush_filter: None,
+            temperature_rate: 5,
+            target_temperature: 0,
+            wind_strength: 0.5,
+            symmetry: Symmetry::new(),
+            stamp: None,
             x_axis_locked: None,
             y_axis_locked: None,
             should_place_particles: false,
             cursor_position: PhysicalPosition::new(0.0, 0.0),
+            cursor_grid_position: (0, 0),
+            brush_grid_position: (0, 0),
             previous_cursor_position: PhysicalPosition::new(0.0, 0.0),
             particle_placement_queue: Vec::new(),
+            shape_start: None,
+            should_finish_shape: false,
+            shape_preview: Vec::new(),
+            should_fill: false,
+            selection: None,
+            is_pasting: false,
+            should_paste: false,
+
+            undo_history: Vec::new(),
+            autosave_world: false,
+            view_mode: ViewMode::Normal,
+            camera: Camera::new(),
+            is_panning: false,
+            pan_anchor: None,
+            queued_zoom: 0.0,
+            screenshot_scale: 1,
+            should_take_screenshot: false,
+            screenshot_status: None,
+            recording: None,
+            recording_format: RecordingFormat::Gif,
+            recording_frame_skip: 1,
+            recording_scale: 1,
+            recording_status: None,
 
             last_window_resize: None,
         }
     }
 
+    pub fn load_settings(&mut self, settings: &Settings) {
+        self.brush_size = settings.brush_size;
+        self.selected_particle = settings.selected_particle;
+        self.autosave_world = settings.autosave_world;
+        self.screenshot_scale = settings.screenshot_scale;
+        self.recording_format = settings.recording_format;
+        self.recording_frame_skip = settings.recording_frame_skip;
+        self.recording_scale = settings.recording_scale;
+    }
+
+    pub fn save_settings(&self, settings: &mut Settings) {
+        settings.brush_size = self.brush_size;
+        settings.selected_particle = self.selected_particle;
+        settings.autosave_world = self.autosave_world;
+        settings.screenshot_scale = self.screenshot_scale;
+        settings.recording_format = self.recording_format;
+        settings.recording_frame_skip = self.recording_frame_skip;
+        settings.recording_scale = self.recording_scale;
+    }
+
     pub fn update(&mut self) {
         while self.frame_time >= TARGET_TIME_PER_UPDATE {
             if !self.is_paused || self.should_update_once {
                 self.should_update_once = false;
                 self.sandbox.update();
+
+                let recording_failed = match &mut self.recording {
+                    Some(recording) => !recording.record_update(&mut self.sandbox, self.view_mode),
+                    None => false,
+                };
+                if recording_failed {
+                    self.stop_recording();
+                }
             }
             self.frame_time -= TARGET_TIME_PER_UPDATE;
         }
     }
 
+    pub fn toggle_recording(&mut self) {
+        if self.recording.is_some() {
+            self.stop_recording();
+            return;
+        }
+
+        match Recording::start(
+            self.recording_format,
+            self.recording_frame_skip,
+            self.recording_scale,
+        ) {
+            Ok(recording) => {
+                self.recording = Some(recording);
+                self.recording_status = None;
+            }
+            Err(error) => {
+                self.recording_status = Some(format!("Failed to start recording: {}", error));
+            }
+        }
+    }
+
+    /// Finish saving the recording, if there is one
+    pub fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            self.recording_status = Some(match recording.finish() {
+                Ok(path) => format!("Saved recording to {}", path.display()),
+                Err(error) => format!("Failed to save recording: {}", error),
+            });
+        }
+    }
+
     pub fn handle_cursor_move(&mut self, new_cursor_position: PhysicalPosition<f64>) {
         self.previous_cursor_position = self.cursor_position;
         self.cursor_position = new_cursor_position;
@@ -71,86 +215,636 @@ impl Game {
         }
     }
 
+    pub fn handle_mouse_input(&mut self, is_pressed: bool) {
+        if self.is_pasting {
+            if is_pressed {
+                self.save_undo_step();
+                self.should_paste = true;
+            }
+            return;
+        }
+
+        if is_pressed && self.tool != Tool::Select {
+            self.save_undo_step();
+        }
+
+        match self.tool {
+            Tool::Freehand
+            | Tool::Heat
+            | Tool::Cool
+            | Tool::SetTemperature
+            | Tool::Drag
+            | Tool::Wind => self.should_place_particles = is_pressed,
+            Tool::Fill => self.should_fill |= is_pressed,
+            _ => {
+                if is_pressed {
+                    self.shape_start = Some(self.cursor_position);
+                } else if self.shape_start.is_some() {
+                    self.should_finish_shape = true;
+                }
+            }
+        }
+    }
+
+    /// Queue zooming in (positive) or out (negative) by a number of scroll wheel lines
+    pub fn handle_mouse_wheel(&mut self, lines: f64) {
+        self.queued_zoom += lines;
+    }
+
+    pub fn handle_pan_input(&mut self, is_pressed: bool) {
+        self.is_panning = is_pressed;
+        self.pan_anchor = None;
+    }
+
     /// Place particles in a straight line from previous_cursor_position to cursor_position
     /// In addition, use data cached from WindowEvent::CursorMoved to ensure all gestures are properly captured
     pub fn place_queued_particles(&mut self, pixels: &Pixels) {
+        self.place_queued_particles_with(|position| window_pos_to_frame(pixels, position));
+    }
+
+    /// Does the work of place_queued_particles, given how to convert window positions to pixels of the frame
+    fn place_queued_particles_with(
+        &mut self,
+        window_pos_to_frame: impl Fn(PhysicalPosition<f64>) -> (usize, usize),
+    ) {
+        let window_pos_to_grid = |camera: &Camera, position: PhysicalPosition<f64>| {
+            camera.frame_to_grid(window_pos_to_frame(position))
+        };
+
+        // Zoom in or out around the cursor
+        let cursor_frame_position = window_pos_to_frame(self.cursor_position);
+        let zoom_steps = self.queued_zoom.trunc();
+        if zoom_steps != 0.0 {
+            self.queued_zoom -= zoom_steps;
+            let zoom = (self.camera.zoom() as isize + zoom_steps as isize).max(1) as usize;
+            self.camera.zoom_at(cursor_frame_position, zoom);
+        }
+        // Pan by keeping the cell that was under the cursor when panning started under it
+        if self.is_panning {
+            let pan_anchor = *self
+                .pan_anchor
+                .get_or_insert(self.camera.frame_to_grid(cursor_frame_position));
+            self.camera.move_cell_to(pan_anchor, cursor_frame_position);
+        }
+
+        // Track which cell the cursor is over
+        self.cursor_grid_position = window_pos_to_grid(&self.camera, self.cursor_position);
+        self.brush_grid_position =
+            window_pos_to_grid(&self.camera, self.lock_to_axes(self.cursor_position));
+
         // Queue current position if should_place_particles
-        if self.should_place_particles {
+        // Tools that move particles only act on cursor movement, so they don't repeat the last movement every frame
+        if self.should_place_particles && !self.tool.moves_particles() {
             self.particle_placement_queue
                 .push((self.previous_cursor_position, self.cursor_position));
         }
+
         // Place each particle
-        for (p1, mut p2) in self.particle_placement_queue.drain(..) {
-            // Adjust coordinates
-            if let Some(locked_x) = self.x_axis_locked {
-                if self.selected_particle != Some(ParticleType::Electricity) {
-                    p2.x = locked_x;
+        let brush_offsets = brush_offsets(self.brush_shape, self.brush_size);
+        for (p1, p2) in std::mem::take(&mut self.particle_placement_queue) {
+            // Project both ends of the segment onto the locked axes, so that consecutive segments join up along them
+            let (p1x, p1y) = window_pos_to_grid(&self.camera, self.lock_to_axes(p1));
+            let (p2x, p2y) = window_pos_to_grid(&self.camera, self.lock_to_axes(p2));
+
+            // Place particles, or move them along each step of the path
+            let p1 = (p1x as isize, p1y as isize);
+            let p2 = (p2x as isize, p2y as isize);
+            match self.tool {
+                Tool::Drag | Tool::Wind => {
+                    for step in line_points(p1, p2).windows(2) {
+                        let direction = (step[1].0 - step[0].0, step[1].1 - step[0].1);
+                        if self.tool == Tool::Drag {
+                            self.drag_particles(step[0], direction, &brush_offsets);
+                        } else {
+                            self.blow_particles(step[1], direction, &brush_offsets);
+                        }
+                    }
+                }
+                _ => {
+                    let p1_images = self.symmetry.images(p1);
+                    let p2_images = self.symmetry.images(p2);
+                    for (p1, p2) in p1_images.into_iter().zip(p2_images) {
+                        for (x, y) in line_points(p1, p2) {
+                            self.apply_brush(x, y, &brush_offsets);
+                        }
+                    }
                 }
             }
-            if let Some(locked_y) = self.y_axis_locked {
-                if self.selected_particle != Some(ParticleType::Electricity) {
-                    p2.y = locked_y;
+        }
+
+        // Preview the shape being dragged out, and place it once the mouse is released
+        self.shape_preview.clear();
+        if let Some(shape_start) = self.shape_start {
+            let (start_x, start_y) = window_pos_to_grid(&self.camera, shape_start);
+            let (end_x, end_y) = self.cursor_grid_position;
+            if self.tool == Tool::Select {
+                self.selection = Some((
+                    start_x.min(end_x),
+                    start_y.min(end_y),
+                    start_x.abs_diff(end_x) + 1,
+                    start_y.abs_diff(end_y) + 1,
+                ));
+            } else if self.tool.is_shape() {
+                self.shape_preview = self.shape_cells(
+                    (start_x as isize, start_y as isize),
+                    (end_x as isize, end_y as isize),
+                );
+            }
+            if self.should_finish_shape {
+                for (x, y) in std::mem::take(&mut self.shape_preview) {
+                    self.place_particle(x, y);
                 }
+                self.shape_start = None;
+                self.should_finish_shape = false;
             }
-            let (p1x, p1y) = pixels
-                .window_pos_to_pixel(p1.into())
-                .unwrap_or_else(|p| pixels.clamp_pixel_pos(p));
-            let (p2x, p2y) = pixels
-                .window_pos_to_pixel(p2.into())
-                .unwrap_or_else(|p| pixels.clamp_pixel_pos(p));
+        }
 
-            // Don't place multiple Electricity vertically
-            let brush_size_x = self.brush_size as usize;
-            let brush_size_y = if self.selected_particle == Some(ParticleType::Electricity) {
-                1
-            } else {
-                self.brush_size as usize
-            };
+        // Flood fill the region under the cursor
+        if self.should_fill {
+            self.should_fill = false;
+            let (x, y) = self.cursor_grid_position;
+            let target_ptype = self.sandbox[x][y].map(|particle| particle.ptype);
+            if target_ptype != self.selected_particle {
+                for (x, y) in flood_fill_points(&self.sandbox, x, y) {
+                    let particle = self
+                        .selected_particle
+                        .map(|ptype| Particle::new(ptype, &mut self.sandbox.rng));
+                    self.sandbox[x][y] = particle;
+                }
+            }
+        }
+
+        // Paste the stamp at the cursor, replacing the whole region under it
+        if self.should_paste {
+            self.should_paste = false;
+            self.is_pasting = false;
+            if let Some(stamp) = &self.stamp {
+                let (origin_x, origin_y) = stamp.origin_offset();
+                for stamp_y in 0..stamp.height {
+                    for stamp_x in 0..stamp.width {
+                        let x = self.cursor_grid_position.0 as isize + origin_x + stamp_x as isize;
+                        let y = self.cursor_grid_position.1 as isize + origin_y + stamp_y as isize;
+                        if (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                            && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                        {
+                            self.sandbox[x as usize][y as usize] = stamp.get(stamp_x, stamp_y);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Move a position onto the locked axes. Axis locks don't apply to Electricity.
+    fn lock_to_axes(&self, mut position: PhysicalPosition<f64>) -> PhysicalPosition<f64> {
+        if self.selected_particle != Some(ParticleType::Electricity) {
+            if let Some(locked_x) = self.x_axis_locked {
+                position.x = locked_x;
+            }
+            if let Some(locked_y) = self.y_axis_locked {
+                position.y = locked_y;
+            }
+        }
+        position
+    }
+
+    /// Returns the cells covered by the current shape tool dragged from start to end.
+    /// Outlines are drawn with the brush, while filled shapes cover exactly their area.
+    fn shape_cells(&self, start: (isize, isize), end: (isize, isize)) -> Vec<(usize, usize)> {
+        let brush_offsets = if self.tool.is_filled() {
+            vec![(0, 0)]
+        } else {
+            brush_offsets(self.brush_shape, self.brush_size)
+        };
+
+        let mut is_covered = vec![false; SANDBOX_WIDTH * SANDBOX_HEIGHT];
+        let mut cells = Vec::new();
+        let points = shape_points(self.tool, start, end)
+            .into_iter()
+            .flat_map(|point| self.symmetry.images(point));
+        for (x, y) in points {
+            for (x_offset, y_offset) in &brush_offsets {
+                // Don't place multiple Electricity vertically
+                if self.selected_particle == Some(ParticleType::Electricity) && *y_offset != 0 {
+                    continue;
+                }
+
+                let x = x + x_offset;
+                let y = y + y_offset;
+                if (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                    && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                {
+                    let (x, y) = (x as usize, y as usize);
+                    if !is_covered[y * SANDBOX_WIDTH + x] {
+                        is_covered[y * SANDBOX_WIDTH + x] = true;
+                        cells.push((x, y));
+                    }
+                }
+            }
+        }
+        cells
+    }
 
-            // Place particles (Bresenham's line algorithm)
-            let (mut p1x, mut p1y) = (p1x as isize, p1y as isize);
-            let (p2x, p2y) = (p2x as isize, p2y as isize);
-            let dx = (p2x - p1x).abs();
-            let sx = if p1x < p2x { 1 } else { -1 };
-            let dy = -(p2y - p1y).abs();
-            let sy = if p1y < p2y { 1 } else { -1 };
-            let mut err = dx + dy;
-            loop {
-                let (x, y) = (p1x as usize, p1y as usize);
-                for x in x..(x + brush_size_x) {
-                    for y in y..(y + brush_size_y) {
-                        if x < SANDBOX_WIDTH && y < SANDBOX_HEIGHT {
-                            match self.selected_particle {
-                                Some(selected_particle) => {
-                                    if self.sandbox[x][y].is_none() {
-                                        self.sandbox[x][y] = Some(Particle::new(
-                                            selected_particle,
-                                            &mut self.sandbox.rng,
-                                        ));
-                                    }
+    /// Apply the brush centered at (x, y)
+    fn apply_brush(&mut self, x: isize, y: isize, brush_offsets: &[(isize, isize)]) {
+        // Place the stamp's particles into empty cells
+        if self.brush_shape == BrushShape::Stamp && self.tool == Tool::Freehand {
+            if let Some(stamp) = &self.stamp {
+                let (origin_x, origin_y) = stamp.origin_offset();
+                for stamp_y in 0..stamp.height {
+                    for stamp_x in 0..stamp.width {
+                        let x = x + origin_x + stamp_x as isize;
+                        let y = y + origin_y + stamp_y as isize;
+                        if (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                            && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                        {
+                            let (x, y) = (x as usize, y as usize);
+                            if let Some(particle) = stamp.get(stamp_x, stamp_y) {
+                                if self.sandbox[x][y].is_none() {
+                                    self.sandbox[x][y] = Some(particle);
                                 }
-                                None => self.sandbox[x][y] = None,
                             }
                         }
                     }
                 }
+            }
+            return;
+        }
 
-                if p1x == p2x && p1y == p2y {
-                    break;
+        for (x_offset, y_offset) in brush_offsets {
+            // Don't place multiple Electricity vertically
+            if self.tool == Tool::Freehand
+                && self.selected_particle == Some(ParticleType::Electricity)
+                && *y_offset != 0
+            {
+                continue;
+            }
+            // Only fill some of the cells when spraying
+            if self.brush_shape == BrushShape::Spray
+                && !self.sandbox.rng.gen_bool(self.spray_density as f64)
+            {
+                continue;
+            }
+
+            let x = x + x_offset;
+            let y = y + y_offset;
+            if (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+            {
+                let (x, y) = (x as usize, y as usize);
+                match self.tool {
+                    Tool::Heat => self.change_temperature(x, y, self.temperature_rate),
+                    Tool::Cool => self.change_temperature(x, y, -self.temperature_rate),
+                    Tool::SetTemperature => self.set_temperature(x, y, self.target_temperature),
+                    _ => self.place_particle(x, y),
                 }
-                let e2 = 2 * err;
-                if e2 >= dy {
-                    err += dy;
-                    p1x += sx;
+            }
+        }
+    }
+
+    /// Move every particle under the brush centered at from one cell in direction.
+    /// Particles in the way are moved into the cells left behind.
+    fn drag_particles(
+        &mut self,
+        from: (isize, isize),
+        direction: (isize, isize),
+        brush_offsets: &[(isize, isize)],
+    ) {
+        let in_bounds = |(x, y): (isize, isize)| {
+            (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+        };
+        let old_cells = brush_offsets
+            .iter()
+            .map(|(x_offset, y_offset)| (from.0 + x_offset, from.1 + y_offset))
+            .filter(|cell| in_bounds(*cell))
+            .collect::<Vec<_>>();
+        let new_cells = old_cells
+            .iter()
+            .map(|(x, y)| (x + direction.0, y + direction.1))
+            .collect::<Vec<_>>();
+        // Don't drag particles past the edge of the sandbox
+        if !new_cells.iter().all(|cell| in_bounds(*cell)) {
+            return;
+        }
+
+        // Pick up the dragged particles, and the particles in the way
+        let old_set = old_cells.iter().copied().collect::<HashSet<_>>();
+        let new_set = new_cells.iter().copied().collect::<HashSet<_>>();
+        let take = |sandbox: &mut Sandbox, (x, y): (isize, isize)| -> Option<Particle> {
+            sandbox[x as usize][y as usize].take()
+        };
+        let dragged = old_cells
+            .iter()
+            .map(|cell| take(&mut self.sandbox, *cell))
+            .collect::<Vec<_>>();
+        let leading_cells = new_cells.iter().filter(|cell| !old_set.contains(cell));
+        let displaced = leading_cells
+            .map(|cell| take(&mut self.sandbox, *cell))
+            .collect::<Vec<_>>();
+
+        // Put the particles in the way into the cells left behind, and the dragged particles into their new cells
+        let trailing_cells = old_cells.iter().filter(|cell| !new_set.contains(cell));
+        for ((x, y), particle) in trailing_cells.zip(displaced) {
+            self.sandbox[*x as usize][*y as usize] = particle;
+        }
+        for ((x, y), particle) in new_cells.iter().zip(dragged) {
+            self.sandbox[*x as usize][*y as usize] = particle;
+        }
+    }
+
+    /// Push the particles under the brush centered at position one cell in direction, if there's room
+    fn blow_particles(
+        &mut self,
+        position: (isize, isize),
+        direction: (isize, isize),
+        brush_offsets: &[(isize, isize)],
+    ) {
+        // Move the particles furthest along the direction first, so they make room for the ones behind them
+        let mut brush_offsets = brush_offsets.to_vec();
+        brush_offsets
+            .sort_by_key(|(x_offset, y_offset)| -(x_offset * direction.0 + y_offset * direction.1));
+
+        for (x_offset, y_offset) in brush_offsets {
+            let (x, y) = (position.0 + x_offset, position.1 + y_offset);
+            let (new_x, new_y) = (x + direction.0, y + direction.1);
+            if !(0..(SANDBOX_WIDTH as isize)).contains(&x)
+                || !(0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                || !(0..(SANDBOX_WIDTH as isize)).contains(&new_x)
+                || !(0..(SANDBOX_HEIGHT as isize)).contains(&new_y)
+            {
+                continue;
+            }
+
+            let (x, y, new_x, new_y) = (x as usize, y as usize, new_x as usize, new_y as usize);
+            if self.sandbox[x][y].is_some()
+                && self.sandbox[new_x][new_y].is_none()
+                && self.sandbox.rng.gen_bool(self.wind_strength)
+            {
+                self.sandbox[new_x][new_y] = self.sandbox[x][y].take();
+            }
+        }
+    }
+
+    /// Heat up or cool down the particle at (x, y), staying within the brush's temperature range
+    fn change_temperature(&mut self, x: usize, y: usize, amount: i16) {
+        if let Some(particle) = self.filtered_particle_mut(x, y) {
+            if amount > 0 && particle.temperature < MAX_BRUSH_TEMPERATURE {
+                particle.temperature = (particle.temperature + amount).min(MAX_BRUSH_TEMPERATURE);
+            }
+            if amount < 0 && particle.temperature > MIN_BRUSH_TEMPERATURE {
+                particle.temperature = (particle.temperature + amount).max(MIN_BRUSH_TEMPERATURE);
+            }
+        }
+    }
+
+    fn set_temperature(&mut self, x: usize, y: usize, temperature: i16) {
+        if let Some(particle) = self.filtered_particle_mut(x, y) {
+            particle.temperature = temperature;
+        }
+    }
+
+    /// Returns the particle at (x, y), unless the brush is filtered to a different particle type
+    fn filtered_particle_mut(&mut self, x: usize, y: usize) -> Option<&mut Particle> {
+        let brush_filter = self.brush_filter;
+        self.sandbox[x][y]
+            .as_mut()
+            .filter(|particle| brush_filter.map_or(true, |ptype| particle.ptype == ptype))
+    }
+
+    /// Place the selected particle at (x, y) if it's empty, or erase it when deleting.
+    /// When the brush is filtered to a particle type, only particles of that type are replaced or erased.
+    fn place_particle(&mut self, x: usize, y: usize) {
+        if let Some(brush_filter) = self.brush_filter {
+            if self.sandbox[x][y].map(|particle| particle.ptype) == Some(brush_filter) {
+                let particle = self
+                    .selected_particle
+                    .map(|ptype| Particle::new(ptype, &mut self.sandbox.rng));
+                self.sandbox[x][y] = particle;
+            }
+            return;
+        }
+
+        match self.selected_particle {
+            Some(selected_particle) => {
+                if self.sandbox[x][y].is_none() {
+                    self.sandbox[x][y] =
+                        Some(Particle::new(selected_particle, &mut self.sandbox.rng));
+                }
+            }
+            None => self.sandbox[x][y] = None,
+        }
+    }
+
+    /// Save the current state of the sandbox, so that the next change can be undone
+    pub fn save_undo_step(&mut self) {
+        let mut cells = if self.undo_history.len() >= MAX_UNDO_STEPS {
+            self.undo_history.remove(0)
+        } else {
+            create_cells_array(None)
+        };
+        cells.copy_from_slice(&self.sandbox.cells[..]);
+        self.undo_history.push(cells);
+    }
+
+    /// Restore the sandbox to how it was before the last change
+    pub fn undo(&mut self) {
+        if let Some(cells) = self.undo_history.pop() {
+            self.sandbox.cells.copy_from_slice(&cells[..]);
+        }
+    }
+
+    pub fn empty_sandbox(&mut self) {
+        self.save_undo_step();
+        self.sandbox.empty_out();
+    }
+
+    /// Copy the selected region into the stamp, so it can be pasted with the Stamp brush
+    pub fn copy_selection(&mut self) {
+        if let Some((x, y, width, height)) = self.selection {
+            self.stamp = Some(Stamp::copy_from(&self.sandbox, x, y, width, height));
+        }
+    }
+
+    /// Copy the selected region into the stamp, and then empty it
+    pub fn cut_selection(&mut self) {
+        if let Some((x, y, width, height)) = self.selection {
+            self.save_undo_step();
+            self.copy_selection();
+            for x in x..(x + width) {
+                for y in y..(y + height) {
+                    self.sandbox[x][y] = None;
+                }
+            }
+        }
+    }
+
+    /// Place the stamp at the cursor on the next click
+    pub fn paste(&mut self) {
+        if self.stamp.is_some() {
+            self.is_pasting = true;
+            self.should_place_particles = false;
+            self.shape_start = None;
+        }
+    }
+
+    /// Save the rendered sandbox if a screenshot was requested, before tool previews are drawn over it
+    pub fn take_queued_screenshot(&mut self, frame: &[u8]) {
+        if !self.should_take_screenshot {
+            return;
+        }
+        self.should_take_screenshot = false;
+
+        let status = match save_screenshot(frame, self.screenshot_scale as usize) {
+            Ok(path) => format!("Saved screenshot to {}", path.display()),
+            Err(error) => format!("Failed to save screenshot: {}", error),
+        };
+        self.screenshot_status = Some((status, Instant::now()));
+    }
+
+    /// Draw tool previews on top of the rendered sandbox
+    pub fn render_overlay(&self, frame: &mut [u8]) {
+        let highlight = if self.selected_particle.is_some() {
+            [255, 255, 255]
+        } else {
+            [255, 60, 60]
+        };
+        for (x, y) in &self.shape_preview {
+            blend_pixel(frame, *x, *y, highlight);
+        }
+
+        // Show the symmetry axes, or the center for radial symmetry
+        let (center_x, center_y) = self.symmetry.center();
+        let axis_color = [80, 160, 255];
+        if let SymmetryMode::Vertical | SymmetryMode::Both = self.symmetry.mode {
+            for y in 0..SANDBOX_HEIGHT {
+                blend_pixel(frame, center_x, y, axis_color);
+            }
+        }
+        if let SymmetryMode::Horizontal | SymmetryMode::Both = self.symmetry.mode {
+            for x in 0..SANDBOX_WIDTH {
+                blend_pixel(frame, x, center_y, axis_color);
+            }
+        }
+        if self.symmetry.mode == SymmetryMode::Radial {
+            for offset in -3..=3 {
+                let x = (center_x as isize + offset).clamp(0, SANDBOX_WIDTH as isize - 1);
+                let y = (center_y as isize + offset).clamp(0, SANDBOX_HEIGHT as isize - 1);
+                blend_pixel(frame, x as usize, center_y, axis_color);
+                blend_pixel(frame, center_x, y as usize, axis_color);
+            }
+        }
+
+        // Show the locked axis as a dashed guide line through the brush
+        let (brush_x, brush_y) = self.brush_grid_position;
+        let guide_color = [120, 255, 120];
+        if self.selected_particle != Some(ParticleType::Electricity) {
+            if self.x_axis_locked.is_some() {
+                for y in (0..SANDBOX_HEIGHT).step_by(2) {
+                    blend_pixel(frame, brush_x, y, guide_color);
+                }
+            }
+            if self.y_axis_locked.is_some() {
+                for x in (0..SANDBOX_WIDTH).step_by(2) {
+                    blend_pixel(frame, x, brush_y, guide_color);
+                }
+            }
+        }
+
+        // Outline the brush at the cursor and its symmetrical copies, in red when erasing
+        let shows_stamp = self.is_pasting
+            || (self.tool == Tool::Freehand && self.brush_shape == BrushShape::Stamp);
+        if self.tool.uses_brush() && !shows_stamp {
+            let outline_color = if self.selected_particle.is_none() && self.tool.places_particles()
+            {
+                [255, 60, 60]
+            } else {
+                [255, 255, 255]
+            };
+            let offsets = brush_offsets(self.brush_shape, self.brush_size)
+                .into_iter()
+                .collect::<HashSet<_>>();
+            let outline = offsets.iter().filter(|(x_offset, y_offset)| {
+                [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                    .iter()
+                    .any(|(dx, dy)| !offsets.contains(&(x_offset + dx, y_offset + dy)))
+            });
+            let images = self.symmetry.images((brush_x as isize, brush_y as isize));
+            for (x_offset, y_offset) in outline {
+                for (x, y) in &images {
+                    let x = x + x_offset;
+                    let y = y + y_offset;
+                    if (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                        && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                    {
+                        blend_pixel(frame, x as usize, y as usize, outline_color);
+                    }
+                }
+            }
+        }
+
+        // Outline the selected region
+        if let (Tool::Select, Some((x, y, width, height))) = (self.tool, self.selection) {
+            let start = (x as isize, y as isize);
+            let end = ((x + width - 1) as isize, (y + height - 1) as isize);
+            for (x, y) in shape_points(Tool::Rectangle, start, end) {
+                blend_pixel(frame, x as usize, y as usize, [255, 220, 0]);
+            }
+        }
+
+        // Show where the stamp will be placed
+        if let (true, Some(stamp)) = (shows_stamp, &self.stamp) {
+            let (origin_x, origin_y) = stamp.origin_offset();
+            for stamp_y in 0..stamp.height {
+                for stamp_x in 0..stamp.width {
+                    let x = self.cursor_grid_position.0 as isize + origin_x + stamp_x as isize;
+                    let y = self.cursor_grid_position.1 as isize + origin_y + stamp_y as isize;
+                    if (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                        && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                    {
+                        if let Some(particle) = stamp.get(stamp_x, stamp_y) {
+                            let (r, g, b) = particle_color(&particle, 0);
+                            blend_pixel(
+                                frame,
+                                x as usize,
+                                y as usize,
+                                [r as u16, g as u16, b as u16],
+                            );
+                        }
+                    }
                 }
-                if e2 <= dx {
-                    err += dx;
-                    p1y += sy;
+            }
+
+            // Outline the region a paste will replace, as the stamp's empty cells are pasted too
+            if self.is_pasting {
+                let start = (
+                    self.cursor_grid_position.0 as isize + origin_x,
+                    self.cursor_grid_position.1 as isize + origin_y,
+                );
+                let end = (
+                    start.0 + stamp.width as isize - 1,
+                    start.1 + stamp.height as isize - 1,
+                );
+                for (x, y) in shape_points(Tool::Rectangle, start, end) {
+                    if (0..(SANDBOX_WIDTH as isize)).contains(&x)
+                        && (0..(SANDBOX_HEIGHT as isize)).contains(&y)
+                    {
+                        blend_pixel(frame, x as usize, y as usize, [255, 220, 0]);
+                    }
                 }
             }
         }
     }
 
+    /// Save the square region under the brush as a stamp, and switch to the Stamp brush
+    pub fn capture_stamp(&mut self) {
+        let size = self.brush_size as usize;
+        let x = self.cursor_grid_position.0.saturating_sub(size / 2);
+        let y = self.cursor_grid_position.1.saturating_sub(size / 2);
+        self.stamp = Some(Stamp::copy_from(&self.sandbox, x, y, size, size));
+        self.brush_shape = BrushShape::Stamp;
+    }
+
     pub fn handle_window_resize(
         &mut self,
         window: &Window,
@@ -184,3 +878,159 @@ impl Game {
         }
     }
 }
+
+/// Convert a position in the window to the pixel of the frame under it, clamped to the frame.
+/// The camera then decides which cell of the sandbox is shown at that pixel.
+fn window_pos_to_frame(pixels: &Pixels, position: PhysicalPosition<f64>) -> (usize, usize) {
+    pixels
+        .window_pos_to_pixel(position.into())
+        .unwrap_or_else(|p| pixels.clamp_pixel_pos(p))
+}
+
+/// Blend a color halfway into the pixel at (x, y)
+fn blend_pixel(frame: &mut [u8], x: usize, y: usize, color: [u16; 3]) {
+    let frame_i = (y * SANDBOX_WIDTH + x) * 4;
+    for c in 0..3 {
+        frame[frame_i + c] = ((frame[frame_i + c] as u16 + color[c]) / 2) as u8;
+    }
+    frame[frame_i + 3] = 255;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Treat window positions as pixels of the frame, as if the window was the same size as the sandbox
+    fn window_pos_to_frame(position: PhysicalPosition<f64>) -> (usize, usize) {
+        (
+            position.x.clamp(0.0, (SANDBOX_WIDTH - 1) as f64) as usize,
+            position.y.clamp(0.0, (SANDBOX_HEIGHT - 1) as f64) as usize,
+        )
+    }
+
+    fn new_game() -> Game {
+        let mut game = Game::new();
+        game.selected_particle = Some(ParticleType::Iridium);
+        game.brush_shape = BrushShape::Square;
+        game.brush_size = 1;
+        game
+    }
+
+    /// Press the mouse at the first point, optionally lock the axes there, and then jump the cursor
+    /// to each of the other points, one frame at a time. Returns every cell that was filled.
+    fn draw_stroke(
+        game: &mut Game,
+        points: &[(f64, f64)],
+        lock_x: bool,
+        lock_y: bool,
+    ) -> HashSet<(usize, usize)> {
+        let (start_x, start_y) = points[0];
+        game.handle_cursor_move(PhysicalPosition::new(start_x, start_y));
+        game.handle_cursor_move(PhysicalPosition::new(start_x, start_y));
+        game.x_axis_locked = lock_x.then_some(start_x);
+        game.y_axis_locked = lock_y.then_some(start_y);
+        game.handle_mouse_input(true);
+        game.place_queued_particles_with(window_pos_to_frame);
+        for (x, y) in &points[1..] {
+            game.handle_cursor_move(PhysicalPosition::new(*x, *y));
+            game.place_queued_particles_with(window_pos_to_frame);
+        }
+        game.handle_mouse_input(false);
+
+        let mut cells = HashSet::new();
+        for x in 0..SANDBOX_WIDTH {
+            for y in 0..SANDBOX_HEIGHT {
+                if game.sandbox[x][y].is_some() {
+                    cells.insert((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    const FAST_ZIGZAG: [(f64, f64); 5] = [
+        (100.0, 20.0),
+        (400.0, 120.0),
+        (10.0, 60.0),
+        (300.0, 250.0),
+        (50.0, 200.0),
+    ];
+
+    #[test]
+    fn x_axis_lock_fills_a_column_without_gaps() {
+        let cells = draw_stroke(&mut new_game(), &FAST_ZIGZAG, true, false);
+        let expected = (20..=250).map(|y| (100, y)).collect::<HashSet<_>>();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn y_axis_lock_fills_a_row_without_gaps() {
+        let cells = draw_stroke(&mut new_game(), &FAST_ZIGZAG, false, true);
+        let expected = (10..=400).map(|x| (x, 20)).collect::<HashSet<_>>();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn both_axis_locks_fill_a_single_cell() {
+        let cells = draw_stroke(&mut new_game(), &FAST_ZIGZAG, true, true);
+        assert_eq!(cells, HashSet::from([(100, 20)]));
+    }
+
+    #[test]
+    fn unlocked_fast_stroke_is_connected() {
+        let cells = draw_stroke(&mut new_game(), &FAST_ZIGZAG, false, false);
+        for (x, y) in FAST_ZIGZAG {
+            assert!(cells.contains(&(x as usize, y as usize)));
+        }
+
+        // Every filled cell should be reachable from the first point through touching cells
+        let mut visited = HashSet::from([(100, 20)]);
+        let mut stack = vec![(100, 20)];
+        while let Some((x, y)) = stack.pop() {
+            for (dx, dy) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                let neighbor = ((x as isize + dx) as usize, (y as isize + dy) as usize);
+                if cells.contains(&neighbor) && visited.insert(neighbor) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+        assert_eq!(visited, cells);
+    }
+
+    #[test]
+    fn paste_replaces_the_region_once() {
+        let mut game = new_game();
+        // A 3x3 stamp with a single Iridium particle in its center
+        game.sandbox[201][201] = Some(Particle::new(ParticleType::Iridium, &mut game.sandbox.rng));
+        game.stamp = Some(Stamp::copy_from(&game.sandbox, 200, 200, 3, 3));
+        game.sandbox.empty_out();
+        for x in 0..60 {
+            for y in 0..60 {
+                game.sandbox[x][y] = Some(Particle::new(ParticleType::Sand, &mut game.sandbox.rng));
+            }
+        }
+
+        // Click and drag while pasting
+        game.paste();
+        let cells = draw_stroke(&mut game, &[(20.0, 20.0), (40.0, 20.0)], false, false);
+        assert!(!game.is_pasting);
+        assert!(game.sandbox[20][20].map(|p| p.ptype) == Some(ParticleType::Iridium));
+        for x in 19..=21 {
+            for y in 19..=21 {
+                if (x, y) != (20, 20) {
+                    assert!(game.sandbox[x][y].is_none());
+                }
+            }
+        }
+        assert_eq!(cells.len(), 60 * 60 - 8);
+    }
+}
@@ -23,3 +23,13 @@ pub fn create_background_array(initial_value: u8) -> Box<[u8; SANDBOX_HEIGHT * S
     let mut data = ManuallyDrop::new(vec![initial_value; SANDBOX_HEIGHT * SANDBOX_WIDTH * 3]);
     unsafe { Box::from_raw(data.as_mut_ptr() as *mut [u8; SANDBOX_HEIGHT * SANDBOX_WIDTH * 3]) }
 }
+
+pub fn create_pheromones_array(initial_value: u8) -> Box<[[u8; SANDBOX_HEIGHT]; SANDBOX_WIDTH]> {
+    let mut data = ManuallyDrop::new(vec![initial_value; SANDBOX_HEIGHT * SANDBOX_WIDTH]);
+    unsafe { Box::from_raw(data.as_mut_ptr() as *mut [[u8; SANDBOX_HEIGHT]; SANDBOX_WIDTH]) }
+}
+
+pub fn create_moved_array(initial_value: bool) -> Box<[[bool; SANDBOX_HEIGHT]; SANDBOX_WIDTH]> {
+    let mut data = ManuallyDrop::new(vec![initial_value; SANDBOX_HEIGHT * SANDBOX_WIDTH]);
+    unsafe { Box::from_raw(data.as_mut_ptr() as *mut [[bool; SANDBOX_HEIGHT]; SANDBOX_WIDTH]) }
+}
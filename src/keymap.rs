@@ -0,0 +1,525 @@
+use crate::particle::ParticleType;
+use crate::tool::Tool;
+use std::fs;
+use std::path::PathBuf;
+use winit::event::{ModifiersState, VirtualKeyCode};
+
+const KEYMAP_FILE_NAME: &str = "keymap.txt";
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    ToggleFullscreen,
+    EmptySandbox,
+    TogglePause,
+    Step,
+    Undo,
+    IncreaseBrushSize,
+    DecreaseBrushSize,
+    CaptureStamp,
+    Copy,
+    Cut,
+    Paste,
+    FlipHorizontally,
+    FlipVertically,
+    Rotate,
+    SetSymmetryCenter,
+    ToggleUI,
+    ToggleFPS,
+    ToggleProfiler,
+    TogglePrefabs,
+    ToggleInspector,
+    ToggleStatistics,
+    ToggleMinimap,
+    ToggleRecordingWindow,
+    CycleViewMode,
+    Screenshot,
+    ToggleRecording,
+    /// None selects the delete tool
+    SelectParticle(Option<ParticleType>),
+    SelectTool(Tool),
+}
+
+/// Actions that don't take an argument, and their names in the keymap file
+const SIMPLE_ACTIONS: [(Action, &str); 27] = [
+    (Action::Quit, "quit"),
+    (Action::ToggleFullscreen, "toggle_fullscreen"),
+    (Action::EmptySandbox, "empty_sandbox"),
+    (Action::TogglePause, "toggle_pause"),
+    (Action::Step, "step"),
+    (Action::Undo, "undo"),
+    (Action::IncreaseBrushSize, "increase_brush_size"),
+    (Action::DecreaseBrushSize, "decrease_brush_size"),
+    (Action::CaptureStamp, "capture_stamp"),
+    (Action::Copy, "copy"),
+    (Action::Cut, "cut"),
+    (Action::Paste, "paste"),
+    (Action::FlipHorizontally, "flip_horizontally"),
+    (Action::FlipVertically, "flip_vertically"),
+    (Action::Rotate, "rotate"),
+    (Action::SetSymmetryCenter, "set_symmetry_center"),
+    (Action::ToggleUI, "toggle_ui"),
+    (Action::ToggleFPS, "toggle_fps"),
+    (Action::ToggleProfiler, "toggle_profiler"),
+    (Action::TogglePrefabs, "toggle_prefabs"),
+    (Action::ToggleInspector, "toggle_inspector"),
+    (Action::ToggleStatistics, "toggle_statistics"),
+    (Action::ToggleMinimap, "toggle_minimap"),
+    (Action::ToggleRecordingWindow, "toggle_recording_window"),
+    (Action::CycleViewMode, "cycle_view_mode"),
+    (Action::Screenshot, "screenshot"),
+    (Action::ToggleRecording, "toggle_recording"),
+];
+
+/// Keys that can be bound, and their names in the keymap file
+const KEYS: [(VirtualKeyCode, &str); 75] = [
+    (VirtualKeyCode::A, "A"),
+    (VirtualKeyCode::B, "B"),
+    (VirtualKeyCode::C, "C"),
+    (VirtualKeyCode::D, "D"),
+    (VirtualKeyCode::E, "E"),
+    (VirtualKeyCode::F, "F"),
+    (VirtualKeyCode::G, "G"),
+    (VirtualKeyCode::H, "H"),
+    (VirtualKeyCode::I, "I"),
+    (VirtualKeyCode::J, "J"),
+    (VirtualKeyCode::K, "K"),
+    (VirtualKeyCode::L, "L"),
+    (VirtualKeyCode::M, "M"),
+    (VirtualKeyCode::N, "N"),
+    (VirtualKeyCode::O, "O"),
+    (VirtualKeyCode::P, "P"),
+    (VirtualKeyCode::Q, "Q"),
+    (VirtualKeyCode::R, "R"),
+    (VirtualKeyCode::S, "S"),
+    (VirtualKeyCode::T, "T"),
+    (VirtualKeyCode::U, "U"),
+    (VirtualKeyCode::V, "V"),
+    (VirtualKeyCode::W, "W"),
+    (VirtualKeyCode::X, "X"),
+    (VirtualKeyCode::Y, "Y"),
+    (VirtualKeyCode::Z, "Z"),
+    (VirtualKeyCode::Key0, "0"),
+    (VirtualKeyCode::Key1, "1"),
+    (VirtualKeyCode::Key2, "2"),
+    (VirtualKeyCode::Key3, "3"),
+    (VirtualKeyCode::Key4, "4"),
+    (VirtualKeyCode::Key5, "5"),
+    (VirtualKeyCode::Key6, "6"),
+    (VirtualKeyCode::Key7, "7"),
+    (VirtualKeyCode::Key8, "8"),
+    (VirtualKeyCode::Key9, "9"),
+    (VirtualKeyCode::F1, "F1"),
+    (VirtualKeyCode::F2, "F2"),
+    (VirtualKeyCode::F3, "F3"),
+    (VirtualKeyCode::F4, "F4"),
+    (VirtualKeyCode::F5, "F5"),
+    (VirtualKeyCode::F6, "F6"),
+    (VirtualKeyCode::F7, "F7"),
+    (VirtualKeyCode::F8, "F8"),
+    (VirtualKeyCode::F9, "F9"),
+    (VirtualKeyCode::F10, "F10"),
+    (VirtualKeyCode::F11, "F11"),
+    (VirtualKeyCode::F12, "F12"),
+    (VirtualKeyCode::Escape, "Escape"),
+    (VirtualKeyCode::Return, "Enter"),
+    (VirtualKeyCode::Back, "Backspace"),
+    (VirtualKeyCode::Space, "Space"),
+    (VirtualKeyCode::Tab, "Tab"),
+    (VirtualKeyCode::Insert, "Insert"),
+    (VirtualKeyCode::Delete, "Delete"),
+    (VirtualKeyCode::Home, "Home"),
+    (VirtualKeyCode::End, "End"),
+    (VirtualKeyCode::PageUp, "PageUp"),
+    (VirtualKeyCode::PageDown, "PageDown"),
+    (VirtualKeyCode::Left, "Left"),
+    (VirtualKeyCode::Right, "Right"),
+    (VirtualKeyCode::Up, "Up"),
+    (VirtualKeyCode::Down, "Down"),
+    (VirtualKeyCode::Period, "Period"),
+    (VirtualKeyCode::Comma, "Comma"),
+    (VirtualKeyCode::Equals, "Equals"),
+    (VirtualKeyCode::Minus, "Minus"),
+    (VirtualKeyCode::Slash, "Slash"),
+    (VirtualKeyCode::Backslash, "Backslash"),
+    (VirtualKeyCode::Semicolon, "Semicolon"),
+    (VirtualKeyCode::Apostrophe, "Apostrophe"),
+    (VirtualKeyCode::LBracket, "LeftBracket"),
+    (VirtualKeyCode::RBracket, "RightBracket"),
+    (VirtualKeyCode::Grave, "Grave"),
+    (VirtualKeyCode::Snapshot, "PrintScreen"),
+];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct KeyCombination {
+    pub key: VirtualKeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyCombination {
+    fn new(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    fn ctrl(key: VirtualKeyCode) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::new(key)
+        }
+    }
+
+    fn shift(key: VirtualKeyCode) -> Self {
+        Self {
+            shift: true,
+            ..Self::new(key)
+        }
+    }
+}
+
+/// Maps key combinations to actions
+pub struct Keymap {
+    bindings: Vec<(KeyCombination, Action)>,
+}
+
+impl Keymap {
+    pub fn new() -> Self {
+        let mut bindings = vec![
+            // Misc controls
+            (KeyCombination::new(VirtualKeyCode::Escape), Action::Quit),
+            (
+                KeyCombination::new(VirtualKeyCode::Return),
+                Action::ToggleFullscreen,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Back),
+                Action::EmptySandbox,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Space),
+                Action::TogglePause,
+            ),
+            (KeyCombination::new(VirtualKeyCode::Period), Action::Step),
+            (KeyCombination::ctrl(VirtualKeyCode::Z), Action::Undo),
+            (
+                KeyCombination::new(VirtualKeyCode::Equals),
+                Action::IncreaseBrushSize,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Minus),
+                Action::DecreaseBrushSize,
+            ),
+            (KeyCombination::new(VirtualKeyCode::K), Action::CaptureStamp),
+            (
+                KeyCombination::new(VirtualKeyCode::X),
+                Action::SetSymmetryCenter,
+            ),
+            (KeyCombination::new(VirtualKeyCode::Key1), Action::ToggleUI),
+            (KeyCombination::new(VirtualKeyCode::Key2), Action::ToggleFPS),
+            (
+                KeyCombination::new(VirtualKeyCode::Key3),
+                Action::ToggleProfiler,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Key4),
+                Action::TogglePrefabs,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Key5),
+                Action::ToggleInspector,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Key6),
+                Action::ToggleStatistics,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Key7),
+                Action::CycleViewMode,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Key8),
+                Action::ToggleMinimap,
+            ),
+            (
+                KeyCombination::new(VirtualKeyCode::Key9),
+                Action::ToggleRecordingWindow,
+            ),
+            (KeyCombination::new(VirtualKeyCode::F12), Action::Screenshot),
+            (
+                KeyCombination::new(VirtualKeyCode::F10),
+                Action::ToggleRecording,
+            ),
+            // Clipboard controls
+            (KeyCombination::ctrl(VirtualKeyCode::C), Action::Copy),
+            (KeyCombination::ctrl(VirtualKeyCode::X), Action::Cut),
+            (KeyCombination::ctrl(VirtualKeyCode::V), Action::Paste),
+            (
+                KeyCombination::ctrl(VirtualKeyCode::H),
+                Action::FlipHorizontally,
+            ),
+            (
+                KeyCombination::ctrl(VirtualKeyCode::J),
+                Action::FlipVertically,
+            ),
+            (KeyCombination::ctrl(VirtualKeyCode::R), Action::Rotate),
+            // Particle selection controls
+            (
+                KeyCombination::new(VirtualKeyCode::D),
+                Action::SelectParticle(None),
+            ),
+        ];
+        let particle_keys = [
+            (VirtualKeyCode::S, ParticleType::Sand),
+            (VirtualKeyCode::W, ParticleType::Water),
+            (VirtualKeyCode::A, ParticleType::Acid),
+            (VirtualKeyCode::I, ParticleType::Iridium),
+            (VirtualKeyCode::R, ParticleType::Replicator),
+            (VirtualKeyCode::P, ParticleType::Plant),
+            (VirtualKeyCode::C, ParticleType::Cryotheum),
+            (VirtualKeyCode::U, ParticleType::Unstable),
+            (VirtualKeyCode::E, ParticleType::Electricity),
+            (VirtualKeyCode::L, ParticleType::Life),
+            (VirtualKeyCode::F, ParticleType::Fire),
+            (VirtualKeyCode::M, ParticleType::Mirror),
+            (VirtualKeyCode::G, ParticleType::Glitch),
+            (VirtualKeyCode::T, ParticleType::Salt),
+            (VirtualKeyCode::N, ParticleType::Snow),
+            (VirtualKeyCode::O, ParticleType::Cloud),
+            (VirtualKeyCode::V, ParticleType::Virus),
+            (VirtualKeyCode::Y, ParticleType::Antidote),
+            (VirtualKeyCode::Q, ParticleType::Ant),
+            (VirtualKeyCode::Z, ParticleType::Seed),
+            (VirtualKeyCode::B, ParticleType::Blood),
+        ];
+        for (key, ptype) in particle_keys {
+            bindings.push((
+                KeyCombination::new(key),
+                Action::SelectParticle(Some(ptype)),
+            ));
+        }
+        let shifted_particle_keys = [
+            (VirtualKeyCode::G, ParticleType::Glass),
+            (VirtualKeyCode::L, ParticleType::SuperLife),
+            (VirtualKeyCode::F, ParticleType::Smoke),
+            (VirtualKeyCode::W, ParticleType::Steam),
+            (VirtualKeyCode::T, ParticleType::SaltWater),
+        ];
+        for (key, ptype) in shifted_particle_keys {
+            bindings.push((
+                KeyCombination::shift(key),
+                Action::SelectParticle(Some(ptype)),
+            ));
+        }
+
+        Self { bindings }
+    }
+
+    /// Load the default keymap, overridden by the keymap file in the user's config directory.
+    /// Returns a list of problems found in the file, such as conflicting bindings.
+    pub fn load() -> (Self, Vec<String>) {
+        let mut keymap = Self::new();
+        let mut problems = Vec::new();
+
+        let path = match keymap_path() {
+            Some(path) => path,
+            None => return (keymap, problems),
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => {
+                // Write out the defaults for the user to edit
+                let _ = fs::create_dir_all(path.parent().unwrap())
+                    .and_then(|_| fs::write(&path, keymap.to_text()));
+                return (keymap, problems);
+            }
+        };
+
+        let mut bound_in_file: Vec<(KeyCombination, usize)> = Vec::new();
+        for (line_i, line) in text.lines().enumerate() {
+            let line_number = line_i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (combination, action) = match line.split_once('=') {
+                Some((combination, action)) => (combination.trim(), action.trim()),
+                None => {
+                    problems.push(format!(
+                        "Line {}: expected \"<keys> = <action>\"",
+                        line_number
+                    ));
+                    continue;
+                }
+            };
+            let combination = match parse_key_combination(combination) {
+                Some(combination) => combination,
+                None => {
+                    problems.push(format!(
+                        "Line {}: unknown keys \"{}\"",
+                        line_number, combination
+                    ));
+                    continue;
+                }
+            };
+            let action = match action {
+                "none" => None,
+                action => match parse_action(action) {
+                    Some(action) => Some(action),
+                    None => {
+                        problems.push(format!(
+                            "Line {}: unknown action \"{}\"",
+                            line_number, action
+                        ));
+                        continue;
+                    }
+                },
+            };
+
+            // Later lines replace earlier ones, but binding the same keys twice is likely a mistake
+            if let Some((_, previous_line_number)) = bound_in_file
+                .iter()
+                .find(|(bound_combination, _)| *bound_combination == combination)
+            {
+                problems.push(format!(
+                    "Line {}: {} is already bound on line {}",
+                    line_number,
+                    format_key_combination(combination),
+                    previous_line_number
+                ));
+            }
+            bound_in_file.push((combination, line_number));
+
+            keymap
+                .bindings
+                .retain(|(bound_combination, _)| *bound_combination != combination);
+            if let Some(action) = action {
+                keymap.bindings.push((combination, action));
+            }
+        }
+
+        (keymap, problems)
+    }
+
+    /// Returns the action bound to a key. If there's no binding with exactly the held modifiers,
+    /// fall back to the binding without modifiers, so that held modifiers like the axis locks don't get in the way.
+    pub fn action(&self, key: VirtualKeyCode, modifiers: ModifiersState) -> Option<Action> {
+        let combination = KeyCombination {
+            key,
+            ctrl: modifiers.ctrl(),
+            shift: modifiers.shift(),
+            alt: modifiers.alt(),
+        };
+        self.bindings
+            .iter()
+            .find(|(bound_combination, _)| *bound_combination == combination)
+            .or_else(|| {
+                self.bindings
+                    .iter()
+                    .find(|(bound_combination, _)| *bound_combination == KeyCombination::new(key))
+            })
+            .map(|(_, action)| *action)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::from(
+            "# Each line binds keys to an action, like \"Ctrl+C = copy\" or \"S = particle Sand\"\n\
+             # Bind keys to \"none\" to unbind them\n",
+        );
+        for (combination, action) in &self.bindings {
+            text.push_str(&format!(
+                "{} = {}\n",
+                format_key_combination(*combination),
+                format_action(*action)
+            ));
+        }
+        text
+    }
+}
+
+fn keymap_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join("sandbox").join(KEYMAP_FILE_NAME))
+}
+
+fn parse_key_combination(text: &str) -> Option<KeyCombination> {
+    let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+    let key = parts.pop()?;
+    let key = KEYS
+        .iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(key))
+        .map(|(key, _)| *key)?;
+
+    let mut combination = KeyCombination::new(key);
+    for modifier in parts {
+        match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" => combination.ctrl = true,
+            "shift" => combination.shift = true,
+            "alt" => combination.alt = true,
+            _ => return None,
+        }
+    }
+    Some(combination)
+}
+
+fn format_key_combination(combination: KeyCombination) -> String {
+    let mut text = String::new();
+    if combination.ctrl {
+        text.push_str("Ctrl+");
+    }
+    if combination.shift {
+        text.push_str("Shift+");
+    }
+    if combination.alt {
+        text.push_str("Alt+");
+    }
+    let key = KEYS
+        .iter()
+        .find(|(key, _)| *key == combination.key)
+        .map(|(_, name)| *name)
+        .unwrap();
+    text.push_str(key);
+    text
+}
+
+fn parse_action(text: &str) -> Option<Action> {
+    if let Some(ptype) = text.strip_prefix("particle ") {
+        let ptype = ptype.trim();
+        if ptype.eq_ignore_ascii_case("delete") {
+            return Some(Action::SelectParticle(None));
+        }
+        return ParticleType::ALL
+            .iter()
+            .find(|p| p.name().eq_ignore_ascii_case(ptype))
+            .map(|p| Action::SelectParticle(Some(*p)));
+    }
+    if let Some(tool) = text.strip_prefix("tool ") {
+        let tool = tool.trim();
+        return Tool::ALL
+            .iter()
+            .find(|t| t.name().eq_ignore_ascii_case(tool))
+            .map(|t| Action::SelectTool(*t));
+    }
+    SIMPLE_ACTIONS
+        .iter()
+        .find(|(_, name)| *name == text)
+        .map(|(action, _)| *action)
+}
+
+fn format_action(action: Action) -> String {
+    match action {
+        Action::SelectParticle(Some(ptype)) => format!("particle {}", ptype.name()),
+        Action::SelectParticle(None) => "particle delete".to_owned(),
+        Action::SelectTool(tool) => format!("tool {}", tool.name()),
+        action => SIMPLE_ACTIONS
+            .iter()
+            .find(|(simple_action, _)| *simple_action == action)
+            .map(|(_, name)| (*name).to_owned())
+            .unwrap(),
+    }
+}
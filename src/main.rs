@@ -1,14 +1,26 @@
 mod behavior;
+mod brush;
+mod camera;
 mod game;
 mod heap_array;
+mod keymap;
 mod particle;
 mod post_process;
+mod prefab;
+mod recording;
 mod sandbox;
+mod screenshot;
+mod settings;
+mod statistics;
+mod symmetry;
+mod tool;
 mod ui;
 
-use crate::particle::ParticleType;
+use crate::brush::MAX_BRUSH_SIZE;
+use crate::keymap::{Action, Keymap};
 use crate::post_process::PostProcess;
 use crate::sandbox::{SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use crate::settings::{load_autosave, save_autosave, Settings};
 use crate::ui::UI;
 use game::Game;
 use pixels::wgpu::BlendState;
@@ -16,27 +28,38 @@ use pixels::{PixelsBuilder, SurfaceTexture};
 use puffin::profile_scope;
 use std::time::Instant;
 use winit::dpi::LogicalSize;
-use winit::event::{ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::event::{
+    ElementState, Event, ModifiersState, MouseButton, MouseScrollDelta, WindowEvent,
+};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::{Fullscreen, Window, WindowBuilder};
 
 fn main() {
     // Setup game
+    let settings = Settings::load();
     let mut game = Game::new();
+    game.load_settings(&settings);
+    if settings.autosave_world {
+        if let Err(error) = load_autosave(&mut game.sandbox) {
+            eprintln!("Failed to restore the autosaved world: {}", error);
+        }
+    }
     let mut last_update = Instant::now();
+    let mut modifiers = ModifiersState::empty();
 
     // Setup windowing
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
         .with_title("Sandbox")
         .with_inner_size(LogicalSize::new(
-            (SANDBOX_WIDTH * 3) as f64,
-            (SANDBOX_HEIGHT * 3) as f64,
+            settings.window_size.0,
+            settings.window_size.1,
         ))
         .with_min_inner_size(LogicalSize::new(
             SANDBOX_WIDTH as f64,
             SANDBOX_HEIGHT as f64,
         ))
+        .with_fullscreen(settings.fullscreen.then(|| Fullscreen::Borderless(None)))
         .build(&event_loop)
         .expect("Failed to create a window");
 
@@ -51,6 +74,11 @@ fn main() {
     let mut post_process =
         PostProcess::new(pixels.device(), surface_size.width, surface_size.height);
     let mut ui = UI::new(&window, pixels.device(), pixels.queue());
+    ui.load_settings(&settings);
+
+    // Load key bindings
+    let (keymap, keymap_problems) = Keymap::load();
+    ui.show_keymap_problems(keymap_problems);
 
     // Handle events
     event_loop.run(move |event, _, control_flow| {
@@ -80,15 +108,30 @@ fn main() {
                 WindowEvent::MouseInput { button, state, .. } => {
                     if *button == MouseButton::Left
                         && (!ui.ui_wants_mouse_input()
+                            || *state == ElementState::Released
                             || game.x_axis_locked.is_some()
                             || game.y_axis_locked.is_some())
                     {
-                        game.should_place_particles = *state == ElementState::Pressed;
+                        game.handle_mouse_input(*state == ElementState::Pressed);
+                    }
+                    if *button == MouseButton::Middle
+                        && (!ui.ui_wants_mouse_input() || *state == ElementState::Released)
+                    {
+                        game.handle_pan_input(*state == ElementState::Pressed);
+                    }
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    if !ui.ui_wants_mouse_input() {
+                        game.handle_mouse_wheel(match delta {
+                            MouseScrollDelta::LineDelta(_, lines) => *lines as f64,
+                            MouseScrollDelta::PixelDelta(position) => position.y / 50.0,
+                        });
                     }
                 }
 
                 // Keyboard events
-                WindowEvent::ModifiersChanged(modifiers) => {
+                WindowEvent::ModifiersChanged(new_modifiers) => {
+                    modifiers = *new_modifiers;
                     game.x_axis_locked = if modifiers.shift() {
                         Some(game.cursor_position.x)
                     } else {
@@ -101,14 +144,11 @@ fn main() {
                     };
                 }
                 WindowEvent::KeyboardInput { input, .. } => {
-                    if input.state == ElementState::Pressed {
-                        handle_key_press(
-                            &input.virtual_keycode,
-                            &window,
-                            control_flow,
-                            &mut game,
-                            &mut ui,
-                        );
+                    let action = input
+                        .virtual_keycode
+                        .and_then(|keycode| keymap.action(keycode, modifiers));
+                    if let (ElementState::Pressed, Some(action)) = (input.state, action) {
+                        handle_action(action, &window, control_flow, &mut game, &mut ui);
                     }
                 }
 
@@ -129,7 +169,10 @@ fn main() {
             // Render
             Event::RedrawRequested(_) => {
                 profile_scope!("render");
-                game.sandbox.render(pixels.get_frame());
+                game.sandbox.render(pixels.get_frame(), game.view_mode);
+                game.take_queued_screenshot(pixels.get_frame());
+                game.render_overlay(pixels.get_frame());
+                game.camera.apply(pixels.get_frame());
 
                 profile_scope!("render_gpu");
                 let _ = pixels.render_with(|encoder, surface_texture, context| {
@@ -140,10 +183,7 @@ fn main() {
                     post_process.render(encoder, surface_texture);
 
                     ui.render(
-                        &mut game.sandbox,
-                        &mut game.selected_particle,
-                        &mut game.brush_size,
-                        &mut game.is_paused,
+                        &mut game,
                         &window,
                         &context.device,
                         &context.queue,
@@ -155,6 +195,28 @@ fn main() {
                 });
             }
 
+            // Remember settings, and optionally the world, for the next launch
+            Event::LoopDestroyed => {
+                game.stop_recording();
+
+                let mut settings = Settings::load();
+                game.save_settings(&mut settings);
+                ui.save_settings(&mut settings);
+                settings.fullscreen = window.fullscreen().is_some();
+                if !settings.fullscreen {
+                    let window_size = window.inner_size().to_logical(window.scale_factor());
+                    settings.window_size = (window_size.width, window_size.height);
+                }
+                if let Err(error) = settings.save() {
+                    eprintln!("Failed to save settings: {}", error);
+                }
+                if game.autosave_world {
+                    if let Err(error) = save_autosave(&game.sandbox) {
+                        eprintln!("Failed to autosave the world: {}", error);
+                    }
+                }
+            }
+
             _ => {}
         }
 
@@ -162,85 +224,88 @@ fn main() {
     });
 }
 
-fn handle_key_press(
-    keycode: &Option<VirtualKeyCode>,
+fn handle_action(
+    action: Action,
     window: &Window,
     control_flow: &mut ControlFlow,
     game: &mut Game,
     ui: &mut UI,
 ) {
-    match keycode {
+    match action {
         // Misc controls
-        Some(VirtualKeyCode::Escape) => *control_flow = ControlFlow::Exit,
-        Some(VirtualKeyCode::Return) => {
+        Action::Quit => *control_flow = ControlFlow::Exit,
+        Action::ToggleFullscreen => {
             let fullscreen = match window.fullscreen() {
                 Some(_) => None,
                 None => Some(Fullscreen::Borderless(window.current_monitor())),
             };
             window.set_fullscreen(fullscreen);
         }
-        Some(VirtualKeyCode::Back) => game.sandbox.empty_out(),
-        Some(VirtualKeyCode::Space) => game.is_paused = !game.is_paused,
-        Some(VirtualKeyCode::Period) if game.is_paused => {
-            game.should_update_once = true;
+        Action::EmptySandbox => game.empty_sandbox(),
+        Action::TogglePause => game.is_paused = !game.is_paused,
+        Action::Step => {
+            if game.is_paused {
+                game.should_update_once = true;
+            }
         }
-        Some(VirtualKeyCode::Equals) => {
-            if game.brush_size < 10 {
+        Action::Undo => game.undo(),
+        Action::IncreaseBrushSize => {
+            if game.brush_size < MAX_BRUSH_SIZE {
                 game.brush_size += 1
             }
         }
-        Some(VirtualKeyCode::Minus) => {
+        Action::DecreaseBrushSize => {
             if game.brush_size > 1 {
                 game.brush_size -= 1
             }
         }
-        Some(VirtualKeyCode::Key1) => ui.toggle_display_ui(),
-        Some(VirtualKeyCode::Key2) => ui.toggle_display_fps(),
-        Some(VirtualKeyCode::Key3) => ui.toggle_display_profiler(),
+        Action::CaptureStamp => game.capture_stamp(),
+        Action::ToggleUI => ui.toggle_display_ui(),
+        Action::ToggleFPS => ui.toggle_display_fps(),
+        Action::ToggleProfiler => ui.toggle_display_profiler(),
+        Action::TogglePrefabs => ui.toggle_display_prefabs(),
+        Action::ToggleInspector => ui.toggle_display_inspector(),
+        Action::ToggleStatistics => ui.toggle_display_statistics(),
+        Action::ToggleMinimap => ui.toggle_display_minimap(),
+        Action::ToggleRecordingWindow => ui.toggle_display_recording(),
+        Action::CycleViewMode => game.view_mode = game.view_mode.next(),
+        Action::Screenshot => game.should_take_screenshot = true,
+        Action::ToggleRecording => game.toggle_recording(),
 
-        // Particle selection controls
-        Some(VirtualKeyCode::D) => {
-            game.selected_particle = None;
-        }
-        Some(VirtualKeyCode::S) => {
-            game.selected_particle = Some(ParticleType::Sand);
-        }
-        Some(VirtualKeyCode::W) => {
-            game.selected_particle = Some(ParticleType::Water);
-        }
-        Some(VirtualKeyCode::A) => {
-            game.selected_particle = Some(ParticleType::Acid);
-        }
-        Some(VirtualKeyCode::I) => {
-            game.selected_particle = Some(ParticleType::Iridium);
-        }
-        Some(VirtualKeyCode::R) => {
-            game.selected_particle = Some(ParticleType::Replicator);
-        }
-        Some(VirtualKeyCode::P) => {
-            game.selected_particle = Some(ParticleType::Plant);
-        }
-        Some(VirtualKeyCode::C) => {
-            game.selected_particle = Some(ParticleType::Cryotheum);
-        }
-        Some(VirtualKeyCode::U) => {
-            game.selected_particle = Some(ParticleType::Unstable);
+        // Clipboard controls
+        Action::Copy => game.copy_selection(),
+        Action::Cut => game.cut_selection(),
+        Action::Paste => game.paste(),
+        Action::FlipHorizontally => {
+            if let Some(stamp) = &mut game.stamp {
+                stamp.flip_horizontally();
+            }
         }
-        Some(VirtualKeyCode::E) => {
-            game.selected_particle = Some(ParticleType::Electricity);
+        Action::FlipVertically => {
+            if let Some(stamp) = &mut game.stamp {
+                stamp.flip_vertically();
+            }
         }
-        Some(VirtualKeyCode::L) => {
-            game.selected_particle = Some(ParticleType::Life);
+        Action::Rotate => {
+            if let Some(stamp) = &mut game.stamp {
+                stamp.rotate();
+            }
         }
-        Some(VirtualKeyCode::F) => {
-            game.selected_particle = Some(ParticleType::Fire);
+
+        // Symmetry controls
+        Action::SetSymmetryCenter => {
+            let (x, y) = game.cursor_grid_position;
+            game.symmetry.set_center(x, y);
         }
-        Some(VirtualKeyCode::M) => {
-            game.selected_particle = Some(ParticleType::Mirror);
+
+        // Particle and tool selection controls
+        Action::SelectParticle(ptype) => {
+            game.selected_particle = ptype;
+            game.is_pasting = false;
         }
-        Some(VirtualKeyCode::G) => {
-            game.selected_particle = Some(ParticleType::Glitch);
+        Action::SelectTool(tool) => {
+            game.tool = tool;
+            game.is_pasting = false;
         }
-        _ => {}
     }
 }
@@ -34,11 +34,52 @@ pub enum ParticleType {
     Mirror,
     Steam,
     Glitch,
+    Salt,
+    SaltWater,
+    Snow,
+    Cloud,
+    Virus,
+    Antidote,
+    Ant,
+    Seed,
+}
+
+/// Groups of particle types, used to organize the particle palette
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ParticleCategory {
+    Powders,
+    Liquids,
+    Gases,
+    Solids,
+    Life,
+    Special,
+}
+
+impl ParticleCategory {
+    pub const ALL: [ParticleCategory; 6] = [
+        ParticleCategory::Powders,
+        ParticleCategory::Liquids,
+        ParticleCategory::Gases,
+        ParticleCategory::Solids,
+        ParticleCategory::Life,
+        ParticleCategory::Special,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParticleCategory::Powders => "Powders",
+            ParticleCategory::Liquids => "Liquids",
+            ParticleCategory::Gases => "Gases",
+            ParticleCategory::Solids => "Solids",
+            ParticleCategory::Life => "Life",
+            ParticleCategory::Special => "Special",
+        }
+    }
 }
 
 impl Distribution<ParticleType> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ParticleType {
-        match rng.gen_range(0..18) {
+        match rng.gen_range(0..26) {
             0 => ParticleType::Sand,
             1 => ParticleType::Water,
             2 => ParticleType::Acid,
@@ -57,11 +98,189 @@ impl Distribution<ParticleType> for Standard {
             15 => ParticleType::Mirror,
             16 => ParticleType::Steam,
             17 => ParticleType::Glitch,
+            18 => ParticleType::Salt,
+            19 => ParticleType::SaltWater,
+            20 => ParticleType::Snow,
+            21 => ParticleType::Cloud,
+            22 => ParticleType::Virus,
+            23 => ParticleType::Antidote,
+            24 => ParticleType::Ant,
+            25 => ParticleType::Seed,
             _ => unreachable!(),
         }
     }
 }
 
+impl ParticleType {
+    /// Every particle type, in declaration order
+    pub const ALL: [ParticleType; 26] = [
+        ParticleType::Sand,
+        ParticleType::Water,
+        ParticleType::Acid,
+        ParticleType::Iridium,
+        ParticleType::Replicator,
+        ParticleType::Plant,
+        ParticleType::Cryotheum,
+        ParticleType::Unstable,
+        ParticleType::Electricity,
+        ParticleType::Glass,
+        ParticleType::Life,
+        ParticleType::SuperLife,
+        ParticleType::Blood,
+        ParticleType::Smoke,
+        ParticleType::Fire,
+        ParticleType::Mirror,
+        ParticleType::Steam,
+        ParticleType::Glitch,
+        ParticleType::Salt,
+        ParticleType::SaltWater,
+        ParticleType::Snow,
+        ParticleType::Cloud,
+        ParticleType::Virus,
+        ParticleType::Antidote,
+        ParticleType::Ant,
+        ParticleType::Seed,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParticleType::Sand => "Sand",
+            ParticleType::Water => "Water",
+            ParticleType::Acid => "Acid",
+            ParticleType::Iridium => "Iridium",
+            ParticleType::Replicator => "Replicator",
+            ParticleType::Plant => "Plant",
+            ParticleType::Cryotheum => "Cryotheum",
+            ParticleType::Unstable => "Unstable",
+            ParticleType::Electricity => "Electricity",
+            ParticleType::Glass => "Glass",
+            ParticleType::Life => "Life",
+            ParticleType::SuperLife => "Super Life",
+            ParticleType::Blood => "Blood",
+            ParticleType::Smoke => "Smoke",
+            ParticleType::Fire => "Fire",
+            ParticleType::Mirror => "Mirror",
+            ParticleType::Steam => "Steam",
+            ParticleType::Glitch => "Glitch",
+            ParticleType::Salt => "Salt",
+            ParticleType::SaltWater => "Saltwater",
+            ParticleType::Snow => "Snow",
+            ParticleType::Cloud => "Cloud",
+            ParticleType::Virus => "Virus",
+            ParticleType::Antidote => "Antidote",
+            ParticleType::Ant => "Ant",
+            ParticleType::Seed => "Seed",
+        }
+    }
+
+    /// The color of this particle type's button, which doesn't depend on any particle's state
+    pub fn color(&self) -> (u8, u8, u8) {
+        match self {
+            ParticleType::Sand => (196, 192, 135),
+            ParticleType::Water => (26, 91, 165),
+            ParticleType::Acid => (148, 219, 10),
+            ParticleType::Iridium => (100, 100, 100),
+            ParticleType::Replicator => (78, 21, 77),
+            ParticleType::Plant => (6, 89, 9),
+            ParticleType::Cryotheum => (12, 193, 255),
+            ParticleType::Unstable => (94, 78, 55),
+            ParticleType::Electricity => (255, 244, 49),
+            ParticleType::Glass => (159, 198, 197),
+            ParticleType::Life => (135, 12, 211),
+            ParticleType::SuperLife => (188, 20, 183),
+            ParticleType::Blood => (122, 14, 27),
+            ParticleType::Smoke => (45, 45, 45),
+            ParticleType::Fire => (255, 151, 20),
+            ParticleType::Mirror => (78, 216, 131),
+            ParticleType::Steam => (50, 150, 150),
+            ParticleType::Glitch => (89, 96, 90),
+            ParticleType::Salt => (225, 225, 218),
+            ParticleType::SaltWater => (48, 109, 178),
+            ParticleType::Snow => (235, 240, 245),
+            ParticleType::Cloud => (200, 200, 210),
+            ParticleType::Virus => (96, 201, 58),
+            ParticleType::Antidote => (236, 138, 190),
+            ParticleType::Ant => (58, 36, 28),
+            ParticleType::Seed => (122, 88, 40),
+        }
+    }
+
+    pub fn category(&self) -> ParticleCategory {
+        match self {
+            ParticleType::Sand => ParticleCategory::Powders,
+            ParticleType::Water => ParticleCategory::Liquids,
+            ParticleType::Acid => ParticleCategory::Liquids,
+            ParticleType::Iridium => ParticleCategory::Solids,
+            ParticleType::Replicator => ParticleCategory::Special,
+            ParticleType::Plant => ParticleCategory::Solids,
+            ParticleType::Cryotheum => ParticleCategory::Solids,
+            ParticleType::Unstable => ParticleCategory::Solids,
+            ParticleType::Electricity => ParticleCategory::Special,
+            ParticleType::Glass => ParticleCategory::Solids,
+            ParticleType::Life => ParticleCategory::Life,
+            ParticleType::SuperLife => ParticleCategory::Life,
+            ParticleType::Blood => ParticleCategory::Liquids,
+            ParticleType::Smoke => ParticleCategory::Gases,
+            ParticleType::Fire => ParticleCategory::Gases,
+            ParticleType::Mirror => ParticleCategory::Special,
+            ParticleType::Steam => ParticleCategory::Gases,
+            ParticleType::Glitch => ParticleCategory::Special,
+            ParticleType::Salt => ParticleCategory::Powders,
+            ParticleType::SaltWater => ParticleCategory::Liquids,
+            ParticleType::Snow => ParticleCategory::Powders,
+            ParticleType::Cloud => ParticleCategory::Gases,
+            ParticleType::Virus => ParticleCategory::Life,
+            ParticleType::Antidote => ParticleCategory::Liquids,
+            ParticleType::Ant => ParticleCategory::Life,
+            ParticleType::Seed => ParticleCategory::Powders,
+        }
+    }
+
+    /// The id stored in the low bits of a Virus particle's extra_data2 to remember what type of particle it infected.
+    /// Returns 0 if this type can't be infected.
+    pub fn virus_host_id(&self) -> i8 {
+        match self {
+            ParticleType::Sand => 0,
+            ParticleType::Water => 0,
+            ParticleType::Acid => 0,
+            ParticleType::Iridium => 0,
+            ParticleType::Replicator => 0,
+            ParticleType::Plant => 1,
+            ParticleType::Cryotheum => 0,
+            ParticleType::Unstable => 0,
+            ParticleType::Electricity => 0,
+            ParticleType::Glass => 0,
+            ParticleType::Life => 2,
+            ParticleType::SuperLife => 3,
+            ParticleType::Blood => 4,
+            ParticleType::Smoke => 0,
+            ParticleType::Fire => 0,
+            ParticleType::Mirror => 0,
+            ParticleType::Steam => 0,
+            ParticleType::Glitch => 0,
+            ParticleType::Salt => 0,
+            ParticleType::SaltWater => 0,
+            ParticleType::Snow => 0,
+            ParticleType::Cloud => 0,
+            ParticleType::Virus => 0,
+            ParticleType::Antidote => 0,
+            ParticleType::Ant => 5,
+            ParticleType::Seed => 0,
+        }
+    }
+
+    pub fn from_virus_host_id(id: i8) -> Option<ParticleType> {
+        match id & VIRUS_HOST_ID_MASK {
+            1 => Some(ParticleType::Plant),
+            2 => Some(ParticleType::Life),
+            3 => Some(ParticleType::SuperLife),
+            4 => Some(ParticleType::Blood),
+            5 => Some(ParticleType::Ant),
+            _ => None,
+        }
+    }
+}
+
 impl Particle {
     pub fn new(ptype: ParticleType, rng: &mut Pcg64) -> Self {
         Self {
@@ -85,6 +304,14 @@ impl Particle {
                 ParticleType::Mirror => 0,
                 ParticleType::Steam => 100,
                 ParticleType::Glitch => 0,
+                ParticleType::Salt => 0,
+                ParticleType::SaltWater => -10,
+                ParticleType::Snow => -90,
+                ParticleType::Cloud => 10,
+                ParticleType::Virus => 0,
+                ParticleType::Antidote => 0,
+                ParticleType::Ant => 0,
+                ParticleType::Seed => 0,
             },
             extra_data1: match ptype {
                 ParticleType::Sand => 0,
@@ -105,6 +332,14 @@ impl Particle {
                 ParticleType::Mirror => 0,
                 ParticleType::Steam => 0,
                 ParticleType::Glitch => 0,
+                ParticleType::Salt => 0,
+                ParticleType::SaltWater => 0,
+                ParticleType::Snow => 0,
+                ParticleType::Cloud => rng.gen_range(0..60),
+                ParticleType::Virus => 0,
+                ParticleType::Antidote => 0,
+                ParticleType::Ant => rng.gen_range(0..8),
+                ParticleType::Seed => 0,
             },
             extra_data2: match ptype {
                 ParticleType::Sand => 0,
@@ -125,6 +360,14 @@ impl Particle {
                 ParticleType::Mirror => 0,
                 ParticleType::Steam => 0,
                 ParticleType::Glitch => 0,
+                ParticleType::Salt => 0,
+                ParticleType::SaltWater => 0,
+                ParticleType::Snow => 0,
+                ParticleType::Cloud => rng.gen_range(30..60),
+                ParticleType::Virus => 0,
+                ParticleType::Antidote => 0,
+                ParticleType::Ant => 0,
+                ParticleType::Seed => 0,
             },
             color_offset: rng.gen_range(-10..11),
             last_update: 0,
@@ -139,6 +382,10 @@ impl Particle {
                     new_position = move_powder(sandbox, x, y);
                 } else if self.extra_data1 == 1 {
                     new_position = move_solid(sandbox, x, y);
+                } else if sandbox.rng.gen_bool(0.2) {
+                    new_position = move_liquid(sandbox, x, y);
+                } else {
+                    new_position = move_solid(sandbox, x, y);
                 }
             }
             ParticleType::Water => {
@@ -152,13 +399,21 @@ impl Particle {
             ParticleType::Iridium => {}
             ParticleType::Replicator => {}
             ParticleType::Plant => {
-                if self.extra_data2 == 0 {
+                if self.extra_data2 == PLANT_LOOSE {
                     new_position = move_powder(sandbox, x, y);
                 }
             }
             ParticleType::Cryotheum => new_position = move_solid(sandbox, x, y),
             ParticleType::Unstable => {}
-            ParticleType::Electricity => new_position = move_electricity(sandbox, x, y),
+            ParticleType::Electricity => {
+                new_position = move_electricity(sandbox, x, y);
+                // Electricity travels through Saltwater twice as fast
+                if new_position != (x, y)
+                    && sandbox[x][y].map(|p| p.ptype) == Some(ParticleType::SaltWater)
+                {
+                    new_position = move_electricity(sandbox, new_position.0, new_position.1);
+                }
+            }
             ParticleType::Glass => {
                 if self.temperature >= 30 {
                     new_position = move_liquid(sandbox, x, y);
@@ -174,6 +429,26 @@ impl Particle {
             ParticleType::Mirror => {}
             ParticleType::Steam => new_position = move_gas(sandbox, x, y),
             ParticleType::Glitch => new_position = move_liquid(sandbox, x, y),
+            ParticleType::Salt => new_position = move_powder(sandbox, x, y),
+            ParticleType::SaltWater => {
+                if self.temperature > -120 {
+                    new_position = move_liquid(sandbox, x, y);
+                } else {
+                    new_position = move_solid(sandbox, x, y);
+                }
+            }
+            ParticleType::Snow => new_position = move_snow(sandbox, x, y),
+            ParticleType::Cloud => new_position = move_cloud(sandbox, x, y),
+            ParticleType::Virus => match ParticleType::from_virus_host_id(self.extra_data2) {
+                Some(ParticleType::Plant) => {}
+                Some(ParticleType::Life)
+                | Some(ParticleType::SuperLife)
+                | Some(ParticleType::Ant) => new_position = move_powder(sandbox, x, y),
+                _ => new_position = move_liquid(sandbox, x, y),
+            },
+            ParticleType::Antidote => new_position = move_liquid(sandbox, x, y),
+            ParticleType::Ant => new_position = move_ant(sandbox, x, y),
+            ParticleType::Seed => new_position = move_powder(sandbox, x, y),
         }
         new_position
     }
@@ -198,6 +473,14 @@ impl Particle {
             ParticleType::Mirror => update_mirror(sandbox, x, y),
             ParticleType::Steam => update_steam(sandbox, x, y),
             ParticleType::Glitch => update_glitch(sandbox, x, y),
+            ParticleType::Salt => update_salt(sandbox, x, y),
+            ParticleType::SaltWater => update_salt_water(sandbox, x, y),
+            ParticleType::Snow => update_snow(sandbox, x, y),
+            ParticleType::Cloud => update_cloud(sandbox, x, y),
+            ParticleType::Virus => update_virus(sandbox, x, y),
+            ParticleType::Antidote => update_antidote(sandbox, x, y),
+            ParticleType::Ant => update_ant(sandbox, x, y),
+            ParticleType::Seed => update_seed(sandbox, x, y),
         }
     }
 
@@ -222,6 +505,14 @@ impl Particle {
             ParticleType::Mirror => 7,
             ParticleType::Steam => 6,
             ParticleType::Glitch => 2,
+            ParticleType::Salt => 3,
+            ParticleType::SaltWater => 5,
+            ParticleType::Snow => 7,
+            ParticleType::Cloud => 6,
+            ParticleType::Virus => 3,
+            ParticleType::Antidote => 4,
+            ParticleType::Ant => 3,
+            ParticleType::Seed => 3,
         };
         assert!(tc > 1);
         tc
@@ -232,8 +523,10 @@ impl Particle {
             ParticleType::Sand => {
                 if self.extra_data1 == 0 {
                     (196, 192, 135)
-                } else {
+                } else if self.extra_data1 == 1 {
                     (166, 162, 105)
+                } else {
+                    (107, 84, 56)
                 }
             }
             ParticleType::Water => (26, 91, 175),
@@ -246,13 +539,20 @@ impl Particle {
                     (88, 31, 107)
                 }
             }
-            ParticleType::Plant => {
-                if self.extra_data1 < 2 {
-                    (6, 89, 9)
-                } else {
-                    (20, 61, 21)
+            ParticleType::Plant => match self.extra_data2 {
+                PLANT_TRUNK => (84, 58, 32),
+                PLANT_BRANCH => (104, 76, 42),
+                PLANT_LEAF => (24, 122, 30),
+                PLANT_FLOWER => (226, 92, 160),
+                stage if stage >= PLANT_WILTED => (122, 98, 54),
+                _ => {
+                    if self.extra_data1 < 2 {
+                        (6, 89, 9)
+                    } else {
+                        (20, 61, 21)
+                    }
                 }
-            }
+            },
             ParticleType::Cryotheum => (12, 191, 201),
             ParticleType::Unstable => (84, 68, 45),
             ParticleType::Electricity => (247, 244, 49),
@@ -294,6 +594,40 @@ impl Particle {
             }
             ParticleType::Steam => (40, 140, 140),
             ParticleType::Glitch => (90, 90, 90),
+            ParticleType::Salt => (225, 225, 218),
+            ParticleType::SaltWater => (48, 109, 178),
+            ParticleType::Snow => (235, 240, 245),
+            ParticleType::Cloud => (200, 200, 210),
+            ParticleType::Virus => {
+                // Blend the color of the infected particle with the Virus's color
+                let virus_color = (96, 201, 58);
+                match ParticleType::from_virus_host_id(self.extra_data2) {
+                    Some(host) => {
+                        let host_color = Particle {
+                            ptype: host,
+                            extra_data1: 0,
+                            extra_data2: 0,
+                            ..*self
+                        }
+                        .base_color();
+                        (
+                            ((host_color.0 as u16 + virus_color.0 as u16) / 2) as u8,
+                            ((host_color.1 as u16 + virus_color.1 as u16) / 2) as u8,
+                            ((host_color.2 as u16 + virus_color.2 as u16) / 2) as u8,
+                        )
+                    }
+                    None => virus_color,
+                }
+            }
+            ParticleType::Antidote => (236, 138, 190),
+            ParticleType::Ant => {
+                if self.extra_data1 < 8 {
+                    (48, 30, 24)
+                } else {
+                    (110, 92, 58)
+                }
+            }
+            ParticleType::Seed => (122, 88, 40),
         }
     }
 
@@ -329,6 +663,14 @@ impl Particle {
             ParticleType::Mirror => 20,
             ParticleType::Steam => 10,
             ParticleType::Glitch => 0,
+            ParticleType::Salt => 10,
+            ParticleType::SaltWater => 30,
+            ParticleType::Snow => 10,
+            ParticleType::Cloud => 10,
+            ParticleType::Virus => 20,
+            ParticleType::Antidote => 30,
+            ParticleType::Ant => 0,
+            ParticleType::Seed => 5,
         }
     }
 
@@ -352,6 +694,14 @@ impl Particle {
             ParticleType::Mirror => false,
             ParticleType::Steam => false,
             ParticleType::Glitch => false,
+            ParticleType::Salt => false,
+            ParticleType::SaltWater => false,
+            ParticleType::Snow => false,
+            ParticleType::Cloud => false,
+            ParticleType::Virus => false,
+            ParticleType::Antidote => false,
+            ParticleType::Ant => false,
+            ParticleType::Seed => false,
         }
     }
 
@@ -375,6 +725,14 @@ impl Particle {
             ParticleType::Mirror => false,
             ParticleType::Steam => true,
             ParticleType::Glitch => true,
+            ParticleType::Salt => true,
+            ParticleType::SaltWater => true,
+            ParticleType::Snow => true,
+            ParticleType::Cloud => false,
+            ParticleType::Virus => true,
+            ParticleType::Antidote => true,
+            ParticleType::Ant => true,
+            ParticleType::Seed => true,
         }
     }
 
@@ -398,6 +756,14 @@ impl Particle {
             ParticleType::Mirror => true,
             ParticleType::Steam => true,
             ParticleType::Glitch => true,
+            ParticleType::Salt => true,
+            ParticleType::SaltWater => true,
+            ParticleType::Snow => true,
+            ParticleType::Cloud => true,
+            ParticleType::Virus => true,
+            ParticleType::Antidote => true,
+            ParticleType::Ant => true,
+            ParticleType::Seed => true,
         }
     }
 
@@ -421,6 +787,14 @@ impl Particle {
             ParticleType::Mirror => true,
             ParticleType::Steam => true,
             ParticleType::Glitch => true,
+            ParticleType::Salt => true,
+            ParticleType::SaltWater => true,
+            ParticleType::Snow => true,
+            ParticleType::Cloud => false,
+            ParticleType::Virus => true,
+            ParticleType::Antidote => true,
+            ParticleType::Ant => true,
+            ParticleType::Seed => true,
         }
     }
 
@@ -444,6 +818,168 @@ impl Particle {
             ParticleType::Mirror => false,
             ParticleType::Steam => false,
             ParticleType::Glitch => true,
+            ParticleType::Salt => false,
+            ParticleType::SaltWater => false,
+            ParticleType::Snow => false,
+            ParticleType::Cloud => false,
+            ParticleType::Virus => true,
+            ParticleType::Antidote => false,
+            ParticleType::Ant => true,
+            ParticleType::Seed => true,
+        }
+    }
+
+    pub fn conducts_electricity(&self) -> bool {
+        match self.ptype {
+            ParticleType::Sand => false,
+            ParticleType::Water => true,
+            ParticleType::Acid => false,
+            ParticleType::Iridium => false,
+            ParticleType::Replicator => false,
+            ParticleType::Plant => false,
+            ParticleType::Cryotheum => false,
+            ParticleType::Unstable => false,
+            ParticleType::Electricity => false,
+            ParticleType::Glass => false,
+            ParticleType::Life => false,
+            ParticleType::SuperLife => false,
+            ParticleType::Blood => false,
+            ParticleType::Smoke => false,
+            ParticleType::Fire => false,
+            ParticleType::Mirror => false,
+            ParticleType::Steam => false,
+            ParticleType::Glitch => false,
+            ParticleType::Salt => false,
+            ParticleType::SaltWater => true,
+            ParticleType::Snow => false,
+            ParticleType::Cloud => false,
+            ParticleType::Virus => false,
+            ParticleType::Antidote => false,
+            ParticleType::Ant => false,
+            ParticleType::Seed => false,
+        }
+    }
+
+    /// Describes what this particle's extra_data1/2 mean, as (label, value) pairs
+    pub fn extra_data_description(&self) -> Vec<(&'static str, String)> {
+        const DIRECTIONS: [&str; 8] = [
+            "Right",
+            "Down Right",
+            "Down",
+            "Down Left",
+            "Left",
+            "Up Left",
+            "Up",
+            "Up Right",
+        ];
+        let frames = |frames: i8| format!("{} frames", frames);
+        match self.ptype {
+            ParticleType::Sand => vec![(
+                "Wetness",
+                match self.extra_data1 {
+                    0 => "Dry",
+                    1 => "Wet",
+                    _ => "Mud",
+                }
+                .to_owned(),
+            )],
+            ParticleType::Replicator => vec![(
+                "Replicating",
+                (if self.extra_data1 == 0 { "No" } else { "Yes" }).to_owned(),
+            )],
+            ParticleType::Plant => {
+                let stage = match self.extra_data2 % PLANT_WILTED {
+                    PLANT_LOOSE => "Loose",
+                    PLANT_GROWABLE => "Growable",
+                    PLANT_TRUNK => "Trunk",
+                    PLANT_BRANCH => "Branch",
+                    PLANT_LEAF => "Leaf",
+                    PLANT_FLOWER => "Flower",
+                    _ => "Unknown",
+                };
+                let stage = if self.extra_data2 >= PLANT_WILTED {
+                    format!("{} (Wilted)", stage)
+                } else {
+                    stage.to_owned()
+                };
+                vec![
+                    ("Growth Left", self.extra_data1.to_string()),
+                    ("Stage", stage),
+                ]
+            }
+            ParticleType::Cryotheum => vec![(
+                "Coldsnap In",
+                if self.extra_data1 == 0 {
+                    "Not Triggered".to_owned()
+                } else {
+                    frames(self.extra_data1 - 1)
+                },
+            )],
+            ParticleType::Unstable => vec![("Heats Up In", frames(30 - self.extra_data1))],
+            ParticleType::Electricity => vec![(
+                "State",
+                (if self.extra_data2 == -1 {
+                    "Fading"
+                } else {
+                    "Travelling"
+                })
+                .to_owned(),
+            )],
+            ParticleType::Life | ParticleType::SuperLife => vec![
+                ("Fall Counter", self.extra_data1.to_string()),
+                (
+                    "State",
+                    (if self.extra_data2 == 0 {
+                        "Alive"
+                    } else {
+                        "Dead"
+                    })
+                    .to_owned(),
+                ),
+            ],
+            ParticleType::Smoke => vec![
+                ("Rises For", frames(self.extra_data1)),
+                ("Fuel", frames(self.extra_data2)),
+            ],
+            ParticleType::Fire => vec![
+                ("Hue", self.extra_data1.to_string()),
+                ("Still For", frames(self.extra_data2)),
+            ],
+            ParticleType::Mirror => vec![("Color Cycle", self.extra_data1.to_string())],
+            ParticleType::Cloud => vec![
+                ("Next Drop In", frames(self.extra_data1)),
+                ("Drops Left", self.extra_data2.to_string()),
+            ],
+            ParticleType::Virus => match ParticleType::from_virus_host_id(self.extra_data2) {
+                Some(host) => vec![
+                    ("Host", host.name().to_owned()),
+                    ("Incubation Left", frames(self.extra_data1)),
+                ],
+                None => vec![("Host", "None".to_owned())],
+            },
+            ParticleType::Ant => vec![
+                (
+                    "Direction",
+                    DIRECTIONS[(self.extra_data1 % 8) as usize].to_owned(),
+                ),
+                (
+                    "Carrying Sand",
+                    (if self.extra_data1 >= 8 { "Yes" } else { "No" }).to_owned(),
+                ),
+                ("Underwater For", frames(self.extra_data2)),
+            ],
+            ParticleType::Water
+            | ParticleType::Acid
+            | ParticleType::Iridium
+            | ParticleType::Glass
+            | ParticleType::Blood
+            | ParticleType::Steam
+            | ParticleType::Glitch
+            | ParticleType::Salt
+            | ParticleType::SaltWater
+            | ParticleType::Snow
+            | ParticleType::Antidote
+            | ParticleType::Seed => Vec::new(),
         }
     }
 }
@@ -0,0 +1,86 @@
+use crate::brush::Stamp;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::PathBuf;
+
+const PREFAB_FILE_EXTENSION: &str = "prefab";
+const PREFAB_FILE_HEADER: &[u8; 8] = b"SBPREFAB";
+const PREFAB_FILE_VERSION: u8 = 1;
+
+/// A named stamp saved to disk, so that it can be reused between sessions
+pub struct Prefab {
+    pub name: String,
+    pub stamp: Stamp,
+}
+
+/// The directory prefabs are saved to, inside the user's data directory
+pub fn prefab_directory() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("sandbox").join("prefabs"))
+}
+
+/// Load every readable prefab from the prefab directory, sorted by name
+pub fn load_prefabs() -> Vec<Prefab> {
+    let entries = match prefab_directory().and_then(|directory| fs::read_dir(directory).ok()) {
+        Some(entries) => entries,
+        None => return Vec::new(),
+    };
+
+    let mut prefabs = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(PREFAB_FILE_EXTENSION) {
+            continue;
+        }
+        let name = match path.file_stem().and_then(|name| name.to_str()) {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        if let Ok(stamp) = File::open(&path).and_then(|file| read_prefab(BufReader::new(file))) {
+            prefabs.push(Prefab { name, stamp });
+        }
+    }
+    prefabs.sort_by(|a, b| a.name.cmp(&b.name));
+    prefabs
+}
+
+pub fn save_prefab(name: &str, stamp: &Stamp) -> io::Result<()> {
+    let path = prefab_path(name)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(PREFAB_FILE_HEADER)?;
+    writer.write_all(&[PREFAB_FILE_VERSION])?;
+    stamp.write_to(&mut writer)?;
+    writer.flush()
+}
+
+pub fn delete_prefab(name: &str) -> io::Result<()> {
+    fs::remove_file(prefab_path(name)?)
+}
+
+fn read_prefab(mut reader: impl Read) -> io::Result<Stamp> {
+    let mut header = [0; 9];
+    reader.read_exact(&mut header)?;
+    if &header[..8] != PREFAB_FILE_HEADER || header[8] != PREFAB_FILE_VERSION {
+        return Err(io::Error::new(ErrorKind::InvalidData, "not a prefab file"));
+    }
+    Stamp::read_from(reader)
+}
+
+/// Prefab names are used as file names, so only allow characters that are safe on every platform
+fn prefab_path(name: &str) -> io::Result<PathBuf> {
+    let name = name.trim();
+    let is_valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_');
+    if !is_valid_name {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "names can only contain letters, numbers, spaces, dashes, and underscores",
+        ));
+    }
+
+    let directory = prefab_directory()
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no user data directory"))?;
+    Ok(directory.join(format!("{}.{}", name, PREFAB_FILE_EXTENSION)))
+}
@@ -0,0 +1,177 @@
+use crate::sandbox::{Sandbox, ViewMode, SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use crate::screenshot::{timestamped_path, upscale_frame, write_png};
+use flume::{bounded as bounded_queue, Sender};
+use gif::{Encoder, Frame, Repeat};
+use std::borrow::Cow;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, ErrorKind};
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+
+pub const MAX_RECORDING_FRAME_SKIP: u8 = 30;
+/// Viewers show GIF frames with a delay under 2 hundredths of a second for much longer,
+/// so GIFs are recorded at 30 frames per second at most
+pub const MIN_GIF_FRAME_SKIP: u8 = 1;
+pub const MAX_RECORDING_SCALE: u8 = 4;
+const UPDATES_PER_SECOND: usize = 60;
+/// How many frames can wait to be encoded before recording slows the game down to let the encoder catch up
+const FRAME_QUEUE_LENGTH: usize = 60;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum RecordingFormat {
+    Gif,
+    /// One numbered PNG per frame, in a new directory
+    PngSequence,
+}
+
+impl RecordingFormat {
+    pub const ALL: [Self; 2] = [Self::Gif, Self::PngSequence];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gif => "GIF",
+            Self::PngSequence => "PNG Sequence",
+        }
+    }
+}
+
+/// Renders the sandbox after every few updates, and saves the frames to the user's pictures directory.
+/// Frames are encoded on another thread, so that recording doesn't stall the game.
+pub struct Recording {
+    frame_sender: Sender<Vec<u8>>,
+    encoder_thread: JoinHandle<io::Result<()>>,
+    path: PathBuf,
+    frame_skip: u8,
+    updates_until_next_frame: u8,
+    pub frames_recorded: usize,
+}
+
+impl Recording {
+    /// Start recording one frame every `frame_skip + 1` updates, scaled up by a whole number
+    pub fn start(format: RecordingFormat, frame_skip: u8, scale: u8) -> io::Result<Self> {
+        let scale = scale.clamp(1, MAX_RECORDING_SCALE) as usize;
+        let frame_skip = match format {
+            RecordingFormat::Gif => frame_skip.max(MIN_GIF_FRAME_SKIP),
+            RecordingFormat::PngSequence => frame_skip,
+        };
+        let (frame_sender, frame_receiver) = bounded_queue::<Vec<u8>>(FRAME_QUEUE_LENGTH);
+
+        let (path, encoder_thread) = match format {
+            RecordingFormat::Gif => {
+                let path = timestamped_path(".gif")?;
+                let mut encoder = Encoder::new(
+                    BufWriter::new(File::create(&path)?),
+                    (SANDBOX_WIDTH * scale) as u16,
+                    (SANDBOX_HEIGHT * scale) as u16,
+                    &[],
+                )
+                .map_err(gif_error)?;
+                encoder.set_repeat(Repeat::Infinite).map_err(gif_error)?;
+
+                let encoder_thread = thread::spawn(move || {
+                    for (i, frame) in frame_receiver.iter().enumerate() {
+                        // Pick the palette from the unscaled frame, and then scale up the palette indices,
+                        // which is much faster than quantizing the scaled frame
+                        let (_, _, data) = upscale_frame(&frame, 1);
+                        let mut frame = Frame::from_rgb_speed(
+                            SANDBOX_WIDTH as u16,
+                            SANDBOX_HEIGHT as u16,
+                            &data,
+                            10,
+                        );
+                        if scale > 1 {
+                            frame.buffer = Cow::Owned(upscale_indices(&frame.buffer, scale));
+                            frame.width = (SANDBOX_WIDTH * scale) as u16;
+                            frame.height = (SANDBOX_HEIGHT * scale) as u16;
+                        }
+                        frame.delay = frame_delay(i, frame_skip);
+                        encoder.write_frame(&frame).map_err(gif_error)?;
+                    }
+                    Ok(())
+                });
+                (path, encoder_thread)
+            }
+            RecordingFormat::PngSequence => {
+                let path = timestamped_path("")?;
+                fs::create_dir_all(&path)?;
+
+                let directory = path.clone();
+                let encoder_thread = thread::spawn(move || {
+                    for (i, frame) in frame_receiver.iter().enumerate() {
+                        let frame_path = directory.join(format!("frame-{:05}.png", i + 1));
+                        write_png(&frame_path, &frame, scale)?;
+                    }
+                    Ok(())
+                });
+                (path, encoder_thread)
+            }
+        };
+
+        Ok(Self {
+            frame_sender,
+            encoder_thread,
+            path,
+            frame_skip,
+            updates_until_next_frame: 0,
+            frames_recorded: 0,
+        })
+    }
+
+    /// Called after every sandbox update. Returns false if the encoder has stopped because of an error.
+    pub fn record_update(&mut self, sandbox: &mut Sandbox, view_mode: ViewMode) -> bool {
+        if self.updates_until_next_frame > 0 {
+            self.updates_until_next_frame -= 1;
+            return true;
+        }
+        self.updates_until_next_frame = self.frame_skip;
+
+        let mut frame = vec![0; SANDBOX_WIDTH * SANDBOX_HEIGHT * 4];
+        sandbox.render(&mut frame, view_mode);
+        self.frames_recorded += 1;
+        self.frame_sender.send(frame).is_ok()
+    }
+
+    /// Wait for every recorded frame to be saved, returning where the recording was saved
+    pub fn finish(self) -> io::Result<PathBuf> {
+        let Self {
+            frame_sender,
+            encoder_thread,
+            path,
+            ..
+        } = self;
+        drop(frame_sender);
+        encoder_thread
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::new(ErrorKind::Other, "the encoder crashed")))?;
+        Ok(path)
+    }
+}
+
+/// How long to show a frame of a GIF for, in hundredths of a second.
+/// The delays are rounded so that they add up to the real time between the first and last frames.
+fn frame_delay(frame: usize, frame_skip: u8) -> u16 {
+    let updates_per_frame = frame_skip as usize + 1;
+    let start = frame * updates_per_frame * 100 / UPDATES_PER_SECOND;
+    let end = (frame + 1) * updates_per_frame * 100 / UPDATES_PER_SECOND;
+    (end - start) as u16
+}
+
+/// Scale up a frame of palette indices with nearest neighbor filtering
+fn upscale_indices(indices: &[u8], scale: usize) -> Vec<u8> {
+    let mut scaled = Vec::with_capacity(indices.len() * scale * scale);
+    for row in indices.chunks(SANDBOX_WIDTH) {
+        let start = scaled.len();
+        for index in row {
+            scaled.extend(std::iter::repeat(*index).take(scale));
+        }
+        let end = scaled.len();
+        for _ in 1..scale {
+            scaled.extend_from_within(start..end);
+        }
+    }
+    scaled
+}
+
+fn gif_error(error: gif::EncodingError) -> io::Error {
+    io::Error::new(ErrorKind::Other, error)
+}
@@ -1,5 +1,8 @@
-use crate::heap_array::{create_background_array, create_cells_array};
+use crate::heap_array::{
+    create_background_array, create_cells_array, create_moved_array, create_pheromones_array,
+};
 use crate::particle::{Particle, ParticleType};
+use crate::statistics::Statistics;
 use flume::{bounded as bounded_queue, Receiver};
 use puffin::profile_scope;
 use rand_pcg::Pcg64;
@@ -11,10 +14,67 @@ use std::time::Instant;
 pub const SANDBOX_WIDTH: usize = 480;
 pub const SANDBOX_HEIGHT: usize = 270;
 
+/// Colors of the heat map view, from coldest to hottest. Temperatures outside the range are clamped.
+pub const HEAT_MAP_GRADIENT: [(i16, (u8, u8, u8)); 6] = [
+    (-300, (200, 240, 255)),
+    (-100, (20, 60, 220)),
+    (0, (20, 20, 20)),
+    (100, (200, 20, 0)),
+    (200, (255, 170, 0)),
+    (300, (255, 255, 220)),
+];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ViewMode {
+    Normal,
+    /// Color particles by temperature only
+    Heat,
+    /// Color particles by type only, with the same colors as the particle buttons
+    Type,
+    /// Highlight the cells particles moved to and from during the last update
+    LastUpdated,
+    /// Highlight Electricity, whether it's still travelling, and the particles it can travel through
+    Charge,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 5] = [
+        ViewMode::Normal,
+        ViewMode::Heat,
+        ViewMode::Type,
+        ViewMode::LastUpdated,
+        ViewMode::Charge,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ViewMode::Normal => "Normal",
+            ViewMode::Heat => "Heat",
+            ViewMode::Type => "Type",
+            ViewMode::LastUpdated => "Last Updated",
+            ViewMode::Charge => "Charge",
+        }
+    }
+
+    /// Returns the next view mode, wrapping around to Normal after the last one
+    pub fn next(&self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|view_mode| view_mode == self)
+            .unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
 pub struct Sandbox {
     pub cells: Box<[[Option<Particle>; SANDBOX_HEIGHT]; SANDBOX_WIDTH]>,
     last_cells: Box<[[Option<Particle>; SANDBOX_HEIGHT]; SANDBOX_WIDTH]>,
+    pub pheromones: Box<[[u8; SANDBOX_HEIGHT]; SANDBOX_WIDTH]>,
+    /// Cells that a particle moved to or from during the last update
+    moved: Box<[[bool; SANDBOX_HEIGHT]; SANDBOX_WIDTH]>,
     pub rng: Pcg64,
+    pub virus_infection_chance: f64,
+    pub statistics: Statistics,
     update_counter: u8,
     background: Box<[u8; SANDBOX_HEIGHT * SANDBOX_WIDTH * 3]>,
     noise_queue_receiver: Receiver<Vec<f32>>,
@@ -86,7 +146,11 @@ impl Sandbox {
         Self {
             cells: create_cells_array(None),
             last_cells: create_cells_array(None),
+            pheromones: create_pheromones_array(0),
+            moved: create_moved_array(false),
             rng: Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96),
+            virus_infection_chance: 0.02,
+            statistics: Statistics::new(),
             update_counter: 1,
             background,
             noise_queue_receiver,
@@ -99,6 +163,11 @@ impl Sandbox {
                 *cell = None;
             }
         }
+        for list in self.pheromones.iter_mut() {
+            for pheromone in list.iter_mut() {
+                *pheromone = 0;
+            }
+        }
         self.update_counter = 1;
     }
 
@@ -107,6 +176,7 @@ impl Sandbox {
         self.move_update();
         self.temperature_update();
         self.state_update();
+        self.pheromone_update();
     }
 
     /// Move particles
@@ -115,11 +185,21 @@ impl Sandbox {
 
         self.update_counter = self.update_counter.checked_add(1).unwrap_or(1);
 
+        for list in self.moved.iter_mut() {
+            for moved in list.iter_mut() {
+                *moved = false;
+            }
+        }
+
         for x in 0..SANDBOX_WIDTH {
             for y in 0..SANDBOX_HEIGHT {
                 if let Some(particle) = self[x][y] {
                     if particle.last_update != self.update_counter {
                         let new_particle_position = particle.move_particle(self, x, y);
+                        if new_particle_position != (x, y) {
+                            self.moved[x][y] = true;
+                            self.moved[new_particle_position.0][new_particle_position.1] = true;
+                        }
                         self[new_particle_position.0][new_particle_position.1]
                             .as_mut()
                             .unwrap()
@@ -130,7 +210,7 @@ impl Sandbox {
         }
     }
 
-    /// Transfer temperature between adjacent particles
+    /// Transfer temperature between adjacent particles, and gather statistics while visiting every particle
     fn temperature_update(&mut self) {
         profile_scope!("temperature_transfer");
 
@@ -139,6 +219,7 @@ impl Sandbox {
         for x in 0..SANDBOX_WIDTH {
             for y in 0..SANDBOX_HEIGHT {
                 if let Some(particle1) = &self.last_cells[x][y] {
+                    self.statistics.add_particle(particle1);
                     let thermal_conductivity = particle1.thermal_conductivity();
                     let temperature = particle1.temperature;
                     if y != SANDBOX_HEIGHT - 1 {
@@ -176,6 +257,7 @@ impl Sandbox {
                 }
             }
         }
+        self.statistics.finish_update();
     }
 
     /// Perform particle interactions and state updates
@@ -195,7 +277,18 @@ impl Sandbox {
         }
     }
 
-    pub fn render(&mut self, frame: &mut [u8]) {
+    /// Fade out pheromone trails left by Ants
+    fn pheromone_update(&mut self) {
+        profile_scope!("pheromone_decay");
+
+        for list in self.pheromones.iter_mut() {
+            for pheromone in list.iter_mut() {
+                *pheromone = pheromone.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut [u8], view_mode: ViewMode) {
         profile_scope!("render_cpu");
 
         let noise = self.noise_queue_receiver.recv().ok();
@@ -203,76 +296,67 @@ impl Sandbox {
         let mut i = 0;
         for y in 0..SANDBOX_HEIGHT {
             for x in 0..SANDBOX_WIDTH {
+                let frame_i = i * 4;
                 if let Some(particle) = &self.cells[x][y] {
-                    // Base color
-                    let base_color = particle.base_color();
-
-                    // Tint blue/red based on temperature, except for Electricity
-                    let mut r = 0;
-                    let mut b = 0;
-                    let mut g = 0;
-                    if particle.ptype != ParticleType::Electricity {
-                        if particle.temperature < 0 {
-                            b = -particle.temperature;
-                            g = -particle.temperature / 30;
-                        } else {
-                            r = particle.temperature;
+                    let (color, alpha) = match view_mode {
+                        ViewMode::Normal => {
+                            // Add foam on top of Water
+                            let mut brightness = 0;
+                            if particle.ptype == ParticleType::Water
+                                && y > 2
+                                && y < SANDBOX_HEIGHT - 1
+                            {
+                                if self.cells[x][y - 1].is_none()
+                                    && self.cells[x][y - 2].is_none()
+                                    && self.cells[x][y - 3].is_none()
+                                    && self.cells[x][y + 1].map(|p| p.ptype)
+                                        == Some(ParticleType::Water)
+                                {
+                                    brightness += 35;
+                                }
+                            }
+
+                            // Darken/Lighten based on noise
+                            brightness += noise
+                                .as_ref()
+                                .map(|noise| {
+                                    let shimmer_intensity = particle.shimmer_intensity();
+                                    (noise[i] * shimmer_intensity as f32) as i16
+                                })
+                                .unwrap_or(0);
+
+                            let alpha = if particle.is_glowing() {
+                                0
+                            } else if particle.ptype == ParticleType::Glitch {
+                                120
+                            } else {
+                                255
+                            };
+                            (particle_color(particle, brightness), alpha)
                         }
-                    }
-
-                    // Add Fire hue and shade
-                    if particle.ptype == ParticleType::Fire {
-                        g += particle.extra_data1 as i16;
-                        r -= (particle.extra_data2 / 3) as i16;
-                        g -= (particle.extra_data2 / 3) as i16;
-                        b -= (particle.extra_data2 / 3) as i16;
-                    }
-
-                    // Add foam on top of Water
-                    if particle.ptype == ParticleType::Water && y > 2 && y < SANDBOX_HEIGHT - 1 {
-                        if self.cells[x][y - 1].is_none()
-                            && self.cells[x][y - 2].is_none()
-                            && self.cells[x][y - 3].is_none()
-                            && self.cells[x][y + 1].map(|p| p.ptype) == Some(ParticleType::Water)
-                        {
-                            r += 35;
-                            g += 35;
-                            b += 35;
+                        ViewMode::Heat => (heat_map_color(particle.temperature), 255),
+                        ViewMode::Type => (particle.ptype.color(), 255),
+                        ViewMode::LastUpdated => {
+                            if self.moved[x][y] {
+                                ((255, 220, 60), 255)
+                            } else {
+                                let (r, g, b) = particle.base_color();
+                                ((r / 3, g / 3, b / 3), 255)
+                            }
                         }
-                    }
-
-                    // Darken/Lighten based on noise
-                    let m = noise
-                        .as_ref()
-                        .map(|noise| {
-                            let shimmer_intensity = particle.shimmer_intensity();
-                            (noise[i] * shimmer_intensity as f32) as i16
-                        })
-                        .unwrap_or(0);
-
-                    // Combine everything together
-                    let r = base_color.0 as i16 + r + m + particle.color_offset as i16;
-                    let g = base_color.1 as i16 + g + m + particle.color_offset as i16;
-                    let b = base_color.2 as i16 + b + m + particle.color_offset as i16;
-                    let color = (
-                        clamp(r, 0, 255) as u8,
-                        clamp(g, 0, 255) as u8,
-                        clamp(b, 0, 255) as u8,
-                    );
-
-                    let frame_i = i * 4;
+                        ViewMode::Charge => (charge_color(particle), 255),
+                    };
                     frame[frame_i] = color.0;
                     frame[frame_i + 1] = color.1;
                     frame[frame_i + 2] = color.2;
-                    frame[frame_i + 3] = if particle.is_glowing() {
-                        0
-                    } else if particle.ptype == ParticleType::Glitch {
-                        120
-                    } else {
-                        255
-                    };
+                    frame[frame_i + 3] = alpha;
+                } else if view_mode == ViewMode::LastUpdated && self.moved[x][y] {
+                    // Cells that a particle moved away from
+                    frame[frame_i] = 110;
+                    frame[frame_i + 1] = 30;
+                    frame[frame_i + 2] = 30;
+                    frame[frame_i + 3] = 255;
                 } else {
-                    let frame_i = i * 4;
                     let background_i = i * 3;
                     frame[frame_i] = self.background[background_i];
                     frame[frame_i + 1] = self.background[background_i + 1];
@@ -300,6 +384,78 @@ impl IndexMut<usize> for Sandbox {
     }
 }
 
+/// Returns the color of a particle, based on its type, temperature, and color offset.
+/// Brightness is added to every channel, and is used for effects that depend on the particle's surroundings.
+pub fn particle_color(particle: &Particle, brightness: i16) -> (u8, u8, u8) {
+    // Base color
+    let base_color = particle.base_color();
+
+    // Tint blue/red based on temperature, except for Electricity
+    let mut r = 0;
+    let mut b = 0;
+    let mut g = 0;
+    if particle.ptype != ParticleType::Electricity {
+        if particle.temperature < 0 {
+            b = -particle.temperature;
+            g = -particle.temperature / 30;
+        } else {
+            r = particle.temperature;
+        }
+    }
+
+    // Add Fire hue and shade
+    if particle.ptype == ParticleType::Fire {
+        g += particle.extra_data1 as i16;
+        r -= (particle.extra_data2 / 3) as i16;
+        g -= (particle.extra_data2 / 3) as i16;
+        b -= (particle.extra_data2 / 3) as i16;
+    }
+
+    // Combine everything together
+    let r = base_color.0 as i16 + r + brightness + particle.color_offset as i16;
+    let g = base_color.1 as i16 + g + brightness + particle.color_offset as i16;
+    let b = base_color.2 as i16 + b + brightness + particle.color_offset as i16;
+    (
+        clamp(r, 0, 255) as u8,
+        clamp(g, 0, 255) as u8,
+        clamp(b, 0, 255) as u8,
+    )
+}
+
+/// Returns the color of a temperature in the heat map view, interpolated between the colors of HEAT_MAP_GRADIENT
+pub fn heat_map_color(temperature: i16) -> (u8, u8, u8) {
+    let (min_temperature, min_color) = HEAT_MAP_GRADIENT[0];
+    if temperature <= min_temperature {
+        return min_color;
+    }
+    for stops in HEAT_MAP_GRADIENT.windows(2) {
+        let ((t1, c1), (t2, c2)) = (stops[0], stops[1]);
+        if temperature <= t2 {
+            let t = (temperature - t1) as f32 / (t2 - t1) as f32;
+            let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            return (lerp(c1.0, c2.0), lerp(c1.1, c2.1), lerp(c1.2, c2.2));
+        }
+    }
+    HEAT_MAP_GRADIENT[HEAT_MAP_GRADIENT.len() - 1].1
+}
+
+/// Returns the color of a particle in the charge view.
+/// Electricity is bright yellow while travelling and dim once it's stuck, and particles that conduct it are blue.
+fn charge_color(particle: &Particle) -> (u8, u8, u8) {
+    if particle.ptype == ParticleType::Electricity {
+        // Electricity that can't move is marked with -1, and disappears on its next update
+        if particle.extra_data2 == -1 {
+            (120, 110, 20)
+        } else {
+            (255, 255, 120)
+        }
+    } else if particle.conducts_electricity() {
+        (30, 70, 160)
+    } else {
+        (45, 45, 45)
+    }
+}
+
 fn clamp(value: i16, min: i16, max: i16) -> i16 {
     assert!(min <= max);
     let mut x = value;
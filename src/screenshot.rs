@@ -0,0 +1,94 @@
+use crate::sandbox::{SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MAX_SCREENSHOT_SCALE: u8 = 8;
+
+/// Save a frame rendered by the sandbox as a PNG in the user's pictures directory, returning where it was saved
+pub fn save_screenshot(frame: &[u8], scale: usize) -> io::Result<PathBuf> {
+    let path = timestamped_path(".png")?;
+    write_png(&path, frame, scale)?;
+    Ok(path)
+}
+
+/// A path in the user's pictures directory named after the current time, ending in the given extension.
+/// A number is added to the name rather than overwriting an earlier file from the same second.
+pub fn timestamped_path(extension: &str) -> io::Result<PathBuf> {
+    let directory = dirs::picture_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no user pictures directory"))?;
+
+    let name = format!("sandbox-{}", timestamp());
+    let mut path = directory.join(format!("{}{}", name, extension));
+    let mut copy = 1;
+    while path.exists() {
+        copy += 1;
+        path = directory.join(format!("{}-{}{}", name, copy, extension));
+    }
+    Ok(path)
+}
+
+/// Write a frame rendered by the sandbox to a PNG, scaled up by a whole number
+pub fn write_png(path: &Path, frame: &[u8], scale: usize) -> io::Result<()> {
+    let (width, height, data) = upscale_frame(frame, scale);
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Scale a frame up with nearest neighbor filtering, returning its size and its pixels as RGB.
+/// The frame's alpha channel only marks glowing particles for post processing, so it's dropped.
+pub fn upscale_frame(frame: &[u8], scale: usize) -> (u32, u32, Vec<u8>) {
+    let scale = scale.max(1);
+    let (width, height) = (SANDBOX_WIDTH * scale, SANDBOX_HEIGHT * scale);
+    let mut data = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        for x in 0..width {
+            let frame_i = ((y / scale) * SANDBOX_WIDTH + (x / scale)) * 4;
+            data.extend_from_slice(&frame[frame_i..(frame_i + 3)]);
+        }
+    }
+    (width as u32, height as u32, data)
+}
+
+/// The current UTC date and time, formatted for use in file names
+fn timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, time) = (seconds / 86400, seconds % 86400);
+
+    // Convert days since 1970-01-01 to a civil date, treating March as the first month of the year
+    // so that leap days fall at the end of it
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
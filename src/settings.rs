@@ -0,0 +1,233 @@
+use crate::brush::{Stamp, MAX_BRUSH_SIZE};
+use crate::particle::ParticleType;
+use crate::recording::{RecordingFormat, MAX_RECORDING_FRAME_SKIP, MAX_RECORDING_SCALE};
+use crate::sandbox::{Sandbox, SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use crate::screenshot::MAX_SCREENSHOT_SCALE;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::PathBuf;
+
+const SETTINGS_FILE_NAME: &str = "settings.txt";
+const AUTOSAVE_FILE_NAME: &str = "autosave.world";
+const WORLD_FILE_HEADER: &[u8; 8] = b"SBWORLD_";
+const WORLD_FILE_VERSION: u8 = 1;
+
+/// Settings that are remembered between sessions
+pub struct Settings {
+    pub brush_size: u8,
+    pub selected_particle: Option<ParticleType>,
+    pub display_ui: bool,
+    pub display_fps: bool,
+    pub display_prefabs: bool,
+    pub display_inspector: bool,
+    pub display_statistics: bool,
+    pub display_minimap: bool,
+    pub display_recording: bool,
+    /// Logical size
+    pub window_size: (f64, f64),
+    pub fullscreen: bool,
+    pub autosave_world: bool,
+    pub screenshot_scale: u8,
+    pub recording_format: RecordingFormat,
+    pub recording_frame_skip: u8,
+    pub recording_scale: u8,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            brush_size: 3,
+            selected_particle: Some(ParticleType::Sand),
+            display_ui: true,
+            display_fps: cfg!(debug_assertions),
+            display_prefabs: false,
+            display_inspector: false,
+            display_statistics: false,
+            display_minimap: false,
+            display_recording: false,
+            window_size: ((SANDBOX_WIDTH * 3) as f64, (SANDBOX_HEIGHT * 3) as f64),
+            fullscreen: false,
+            autosave_world: false,
+            screenshot_scale: 1,
+            recording_format: RecordingFormat::Gif,
+            recording_frame_skip: 1,
+            recording_scale: 1,
+        }
+    }
+
+    /// Load the settings file from the user's config directory.
+    /// Missing or invalid settings are left at their defaults.
+    pub fn load() -> Self {
+        let mut settings = Self::new();
+        let text = match settings_directory()
+            .and_then(|directory| fs::read_to_string(directory.join(SETTINGS_FILE_NAME)).ok())
+        {
+            Some(text) => text,
+            None => return settings,
+        };
+
+        for line in text.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => continue,
+            };
+            match key {
+                "brush_size" => {
+                    if let Ok(brush_size) = value.parse::<u8>() {
+                        settings.brush_size = brush_size.clamp(1, MAX_BRUSH_SIZE);
+                    }
+                }
+                "selected_particle" => {
+                    if value == "delete" {
+                        settings.selected_particle = None;
+                    } else if let Some(ptype) =
+                        ParticleType::ALL.iter().find(|ptype| ptype.name() == value)
+                    {
+                        settings.selected_particle = Some(*ptype);
+                    }
+                }
+                "display_ui" => parse_bool(value, &mut settings.display_ui),
+                "display_fps" => parse_bool(value, &mut settings.display_fps),
+                "display_prefabs" => parse_bool(value, &mut settings.display_prefabs),
+                "display_inspector" => parse_bool(value, &mut settings.display_inspector),
+                "display_statistics" => parse_bool(value, &mut settings.display_statistics),
+                "display_minimap" => parse_bool(value, &mut settings.display_minimap),
+                "display_recording" => parse_bool(value, &mut settings.display_recording),
+                "window_width" => {
+                    if let Ok(width) = value.parse::<f64>() {
+                        settings.window_size.0 = width.max(SANDBOX_WIDTH as f64);
+                    }
+                }
+                "window_height" => {
+                    if let Ok(height) = value.parse::<f64>() {
+                        settings.window_size.1 = height.max(SANDBOX_HEIGHT as f64);
+                    }
+                }
+                "fullscreen" => parse_bool(value, &mut settings.fullscreen),
+                "autosave_world" => parse_bool(value, &mut settings.autosave_world),
+                "screenshot_scale" => {
+                    if let Ok(scale) = value.parse::<u8>() {
+                        settings.screenshot_scale = scale.clamp(1, MAX_SCREENSHOT_SCALE);
+                    }
+                }
+                "recording_format" => {
+                    if let Some(format) = RecordingFormat::ALL
+                        .iter()
+                        .find(|format| format.name() == value)
+                    {
+                        settings.recording_format = *format;
+                    }
+                }
+                "recording_frame_skip" => {
+                    if let Ok(frame_skip) = value.parse::<u8>() {
+                        settings.recording_frame_skip = frame_skip.min(MAX_RECORDING_FRAME_SKIP);
+                    }
+                }
+                "recording_scale" => {
+                    if let Ok(scale) = value.parse::<u8>() {
+                        settings.recording_scale = scale.clamp(1, MAX_RECORDING_SCALE);
+                    }
+                }
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let directory = settings_directory()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no user config directory"))?;
+        fs::create_dir_all(&directory)?;
+
+        let selected_particle = self
+            .selected_particle
+            .map(|ptype| ptype.name())
+            .unwrap_or("delete");
+        let text = format!(
+            "brush_size = {}\n\
+             selected_particle = {}\n\
+             display_ui = {}\n\
+             display_fps = {}\n\
+             display_prefabs = {}\n\
+             display_inspector = {}\n\
+             display_statistics = {}\n\
+             display_minimap = {}\n\
+             display_recording = {}\n\
+             window_width = {}\n\
+             window_height = {}\n\
+             fullscreen = {}\n\
+             autosave_world = {}\n\
+             screenshot_scale = {}\n\
+             recording_format = {}\n\
+             recording_frame_skip = {}\n\
+             recording_scale = {}\n",
+            self.brush_size,
+            selected_particle,
+            self.display_ui,
+            self.display_fps,
+            self.display_prefabs,
+            self.display_inspector,
+            self.display_statistics,
+            self.display_minimap,
+            self.display_recording,
+            self.window_size.0,
+            self.window_size.1,
+            self.fullscreen,
+            self.autosave_world,
+            self.screenshot_scale,
+            self.recording_format.name(),
+            self.recording_frame_skip,
+            self.recording_scale,
+        );
+        fs::write(directory.join(SETTINGS_FILE_NAME), text)
+    }
+}
+
+/// The directory settings are saved to, inside the user's config directory
+pub fn settings_directory() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join("sandbox"))
+}
+
+/// Save every particle in the sandbox, so that it can be restored on the next launch
+pub fn save_autosave(sandbox: &Sandbox) -> io::Result<()> {
+    let path = autosave_path()?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(WORLD_FILE_HEADER)?;
+    writer.write_all(&[WORLD_FILE_VERSION])?;
+    Stamp::copy_from(sandbox, 0, 0, SANDBOX_WIDTH, SANDBOX_HEIGHT).write_to(&mut writer)?;
+    writer.flush()
+}
+
+/// Replace the particles in the sandbox with the ones from the last autosave
+pub fn load_autosave(sandbox: &mut Sandbox) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(autosave_path()?)?);
+    let mut header = [0; 9];
+    reader.read_exact(&mut header)?;
+    if &header[..8] != WORLD_FILE_HEADER || header[8] != WORLD_FILE_VERSION {
+        return Err(io::Error::new(ErrorKind::InvalidData, "not a world file"));
+    }
+    let stamp = Stamp::read_from(reader)?;
+    if stamp.width != SANDBOX_WIDTH || stamp.height != SANDBOX_HEIGHT {
+        return Err(io::Error::new(ErrorKind::InvalidData, "wrong world size"));
+    }
+
+    for x in 0..SANDBOX_WIDTH {
+        for y in 0..SANDBOX_HEIGHT {
+            sandbox[x][y] = stamp.get(x, y);
+        }
+    }
+    Ok(())
+}
+
+fn autosave_path() -> io::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|directory| directory.join("sandbox").join(AUTOSAVE_FILE_NAME))
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no user data directory"))
+}
+
+fn parse_bool(value: &str, setting: &mut bool) {
+    if let Ok(value) = value.parse() {
+        *setting = value;
+    }
+}
@@ -0,0 +1,114 @@
+use crate::particle::{Particle, ParticleType};
+use std::collections::VecDeque;
+
+/// How many updates are averaged into each point of the history graphs (one second)
+const UPDATES_PER_SAMPLE: u32 = 60;
+/// How many points the history graphs keep (three minutes)
+pub const HISTORY_LENGTH: usize = 180;
+
+#[derive(Copy, Clone)]
+pub struct StatisticsSample {
+    /// Indexed by ParticleType, in declaration order
+    pub counts: [u32; ParticleType::ALL.len()],
+    pub total: u32,
+    pub average_temperature: f32,
+    pub max_temperature: i16,
+}
+
+impl StatisticsSample {
+    fn new() -> Self {
+        Self {
+            counts: [0; ParticleType::ALL.len()],
+            total: 0,
+            average_temperature: 0.0,
+            max_temperature: 0,
+        }
+    }
+}
+
+/// Particle counts and temperatures, gathered while the sandbox updates so that no extra pass over the cells is needed
+pub struct Statistics {
+    /// The statistics from the last update
+    pub current: StatisticsSample,
+    /// One sample per second, oldest first
+    pub history: VecDeque<StatisticsSample>,
+
+    counts: [u32; ParticleType::ALL.len()],
+    temperature_sum: i64,
+    max_temperature: i16,
+
+    sample_sum: StatisticsSample,
+    updates_in_sample: u32,
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        Self {
+            current: StatisticsSample::new(),
+            history: VecDeque::with_capacity(HISTORY_LENGTH),
+
+            counts: [0; ParticleType::ALL.len()],
+            temperature_sum: 0,
+            max_temperature: i16::MIN,
+
+            sample_sum: StatisticsSample::new(),
+            updates_in_sample: 0,
+        }
+    }
+
+    /// Called once for every particle in the sandbox each update
+    pub fn add_particle(&mut self, particle: &Particle) {
+        self.counts[particle.ptype as usize] += 1;
+        self.temperature_sum += particle.temperature as i64;
+        self.max_temperature = self.max_temperature.max(particle.temperature);
+    }
+
+    /// Called after every particle has been added, to publish this update's statistics and start counting again
+    pub fn finish_update(&mut self) {
+        let total = self.counts.iter().sum();
+        self.current = StatisticsSample {
+            counts: self.counts,
+            total,
+            average_temperature: if total == 0 {
+                0.0
+            } else {
+                self.temperature_sum as f32 / total as f32
+            },
+            max_temperature: if total == 0 { 0 } else { self.max_temperature },
+        };
+        self.counts = [0; ParticleType::ALL.len()];
+        self.temperature_sum = 0;
+        self.max_temperature = i16::MIN;
+
+        // Accumulate this update into the current history sample
+        for (sum, count) in self.sample_sum.counts.iter_mut().zip(self.current.counts) {
+            *sum += count;
+        }
+        self.sample_sum.total += self.current.total;
+        self.sample_sum.average_temperature += self.current.average_temperature;
+        self.sample_sum.max_temperature = if self.updates_in_sample == 0 {
+            self.current.max_temperature
+        } else {
+            self.sample_sum
+                .max_temperature
+                .max(self.current.max_temperature)
+        };
+        self.updates_in_sample += 1;
+
+        if self.updates_in_sample == UPDATES_PER_SAMPLE {
+            let mut sample = self.sample_sum;
+            for count in sample.counts.iter_mut() {
+                *count /= UPDATES_PER_SAMPLE;
+            }
+            sample.total /= UPDATES_PER_SAMPLE;
+            sample.average_temperature /= UPDATES_PER_SAMPLE as f32;
+
+            if self.history.len() == HISTORY_LENGTH {
+                self.history.pop_front();
+            }
+            self.history.push_back(sample);
+            self.sample_sum = StatisticsSample::new();
+            self.updates_in_sample = 0;
+        }
+    }
+}
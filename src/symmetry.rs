@@ -0,0 +1,74 @@
+use crate::sandbox::{SANDBOX_HEIGHT, SANDBOX_WIDTH};
+use std::f64::consts::TAU;
+
+pub const MAX_RADIAL_FOLDS: u8 = 12;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SymmetryMode {
+    Off,
+    Vertical,
+    Horizontal,
+    Both,
+    Radial,
+}
+
+/// Mirrors strokes across the axes through a center point, or repeats them around it
+pub struct Symmetry {
+    pub mode: SymmetryMode,
+    pub radial_folds: u8,
+    /// Stored at double resolution, so that the center can lie between two cells
+    doubled_center: (isize, isize),
+}
+
+impl Symmetry {
+    pub fn new() -> Self {
+        Self {
+            mode: SymmetryMode::Off,
+            radial_folds: 6,
+            doubled_center: (SANDBOX_WIDTH as isize - 1, SANDBOX_HEIGHT as isize - 1),
+        }
+    }
+
+    /// Returns the cell the center lies on (or the cell to the top left of it)
+    pub fn center(&self) -> (usize, usize) {
+        (
+            (self.doubled_center.0 / 2) as usize,
+            (self.doubled_center.1 / 2) as usize,
+        )
+    }
+
+    pub fn set_center(&mut self, x: usize, y: usize) {
+        self.doubled_center = (x as isize * 2, y as isize * 2);
+    }
+
+    /// Returns the point, followed by each of its symmetrical copies
+    pub fn images(&self, (x, y): (isize, isize)) -> Vec<(isize, isize)> {
+        let (center_x, center_y) = self.doubled_center;
+        match self.mode {
+            SymmetryMode::Off => vec![(x, y)],
+            SymmetryMode::Vertical => vec![(x, y), (center_x - x, y)],
+            SymmetryMode::Horizontal => vec![(x, y), (x, center_y - y)],
+            SymmetryMode::Both => vec![
+                (x, y),
+                (center_x - x, y),
+                (x, center_y - y),
+                (center_x - x, center_y - y),
+            ],
+            SymmetryMode::Radial => {
+                let center_x = center_x as f64 / 2.0;
+                let center_y = center_y as f64 / 2.0;
+                let dx = x as f64 - center_x;
+                let dy = y as f64 - center_y;
+                (0..self.radial_folds)
+                    .map(|i| {
+                        let (sin, cos) = (TAU * i as f64 / self.radial_folds as f64).sin_cos();
+                        (
+                            (center_x + dx * cos - dy * sin).round() as isize,
+                            (center_y + dx * sin + dy * cos).round() as isize,
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::sandbox::{Sandbox, SANDBOX_HEIGHT, SANDBOX_WIDTH};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Tool {
+    Freehand,
+    Line,
+    Rectangle,
+    FilledRectangle,
+    Ellipse,
+    FilledEllipse,
+    Fill,
+    Heat,
+    Cool,
+    SetTemperature,
+    Select,
+    Drag,
+    Wind,
+}
+
+impl Tool {
+    /// Every tool, in declaration order
+    pub const ALL: [Tool; 13] = [
+        Tool::Freehand,
+        Tool::Line,
+        Tool::Rectangle,
+        Tool::FilledRectangle,
+        Tool::Ellipse,
+        Tool::FilledEllipse,
+        Tool::Fill,
+        Tool::Heat,
+        Tool::Cool,
+        Tool::SetTemperature,
+        Tool::Select,
+        Tool::Drag,
+        Tool::Wind,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::Freehand => "Freehand",
+            Tool::Line => "Line",
+            Tool::Rectangle => "Rectangle",
+            Tool::FilledRectangle => "Filled Rectangle",
+            Tool::Ellipse => "Ellipse",
+            Tool::FilledEllipse => "Filled Ellipse",
+            Tool::Fill => "Fill",
+            Tool::Heat => "Heat",
+            Tool::Cool => "Cool",
+            Tool::SetTemperature => "Set Temperature",
+            Tool::Select => "Select",
+            Tool::Drag => "Drag",
+            Tool::Wind => "Wind",
+        }
+    }
+
+    /// Shape tools are dragged out from where the mouse was pressed to where it's released
+    pub fn is_shape(&self) -> bool {
+        matches!(
+            self,
+            Tool::Line
+                | Tool::Rectangle
+                | Tool::FilledRectangle
+                | Tool::Ellipse
+                | Tool::FilledEllipse
+        )
+    }
+
+    /// Tools that move existing particles along the cursor's path
+    pub fn moves_particles(&self) -> bool {
+        matches!(self, Tool::Drag | Tool::Wind)
+    }
+
+    /// Filled shapes ignore the brush, while outlines are drawn with it
+    pub fn is_filled(&self) -> bool {
+        matches!(self, Tool::FilledRectangle | Tool::FilledEllipse)
+    }
+
+    /// Tools that act on the cells under the brush
+    pub fn uses_brush(&self) -> bool {
+        !matches!(
+            self,
+            Tool::Fill | Tool::Select | Tool::FilledRectangle | Tool::FilledEllipse
+        )
+    }
+
+    /// Tools that place the selected particle, or erase particles when the Delete Tool is selected
+    pub fn places_particles(&self) -> bool {
+        !matches!(
+            self,
+            Tool::Heat | Tool::Cool | Tool::SetTemperature | Tool::Select | Tool::Drag | Tool::Wind
+        )
+    }
+}
+
+/// Returns every point on a straight line from p1 to p2 (Bresenham's line algorithm)
+pub fn line_points(p1: (isize, isize), p2: (isize, isize)) -> Vec<(isize, isize)> {
+    let (mut x, mut y) = p1;
+    let dx = (p2.0 - x).abs();
+    let sx = if x < p2.0 { 1 } else { -1 };
+    let dy = -(p2.1 - y).abs();
+    let sy = if y < p2.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    let mut points = Vec::with_capacity(dx.max(-dy) as usize + 1);
+    loop {
+        points.push((x, y));
+
+        if x == p2.0 && y == p2.1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+    points
+}
+
+/// Returns the points making up a shape tool's shape, dragged from start to end.
+/// Outlines are one cell thick, and are widened by the brush afterwards.
+pub fn shape_points(tool: Tool, start: (isize, isize), end: (isize, isize)) -> Vec<(isize, isize)> {
+    let (min_x, max_x) = (start.0.min(end.0), start.0.max(end.0));
+    let (min_y, max_y) = (start.1.min(end.1), start.1.max(end.1));
+
+    let mut points = Vec::new();
+    match tool {
+        Tool::Line => points = line_points(start, end),
+        Tool::Rectangle | Tool::FilledRectangle => {
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let is_edge = x == min_x || x == max_x || y == min_y || y == max_y;
+                    if is_edge || tool == Tool::FilledRectangle {
+                        points.push((x, y));
+                    }
+                }
+            }
+        }
+        Tool::Ellipse | Tool::FilledEllipse => {
+            let center_x = (min_x + max_x) as f64 / 2.0;
+            let center_y = (min_y + max_y) as f64 / 2.0;
+            let radius_x = (max_x - min_x) as f64 / 2.0 + 0.5;
+            let radius_y = (max_y - min_y) as f64 / 2.0 + 0.5;
+            let is_inside = |x: isize, y: isize| {
+                let dx = (x as f64 - center_x) / radius_x;
+                let dy = (y as f64 - center_y) / radius_y;
+                dx * dx + dy * dy <= 1.0
+            };
+
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    if !is_inside(x, y) {
+                        continue;
+                    }
+                    // Cells inside the ellipse are on the outline if any of their neighbors aren't
+                    let is_edge = !is_inside(x - 1, y)
+                        || !is_inside(x + 1, y)
+                        || !is_inside(x, y - 1)
+                        || !is_inside(x, y + 1);
+                    if is_edge || tool == Tool::FilledEllipse {
+                        points.push((x, y));
+                    }
+                }
+            }
+        }
+        Tool::Freehand
+        | Tool::Fill
+        | Tool::Heat
+        | Tool::Cool
+        | Tool::SetTemperature
+        | Tool::Select
+        | Tool::Drag
+        | Tool::Wind => {}
+    }
+    points
+}
+
+/// Returns every cell connected to (x, y) that is either empty, or the same type as (x, y)
+pub fn flood_fill_points(sandbox: &Sandbox, x: usize, y: usize) -> Vec<(usize, usize)> {
+    let target_ptype = sandbox[x][y].map(|particle| particle.ptype);
+    let mut visited = vec![false; SANDBOX_WIDTH * SANDBOX_HEIGHT];
+    let mut stack = vec![(x, y)];
+    visited[y * SANDBOX_WIDTH + x] = true;
+
+    let mut points = Vec::new();
+    while let Some((x, y)) = stack.pop() {
+        points.push((x, y));
+
+        let mut visit = |x: usize, y: usize| {
+            if !visited[y * SANDBOX_WIDTH + x]
+                && sandbox[x][y].map(|particle| particle.ptype) == target_ptype
+            {
+                visited[y * SANDBOX_WIDTH + x] = true;
+                stack.push((x, y));
+            }
+        };
+        if x > 0 {
+            visit(x - 1, y);
+        }
+        if x < SANDBOX_WIDTH - 1 {
+            visit(x + 1, y);
+        }
+        if y > 0 {
+            visit(x, y - 1);
+        }
+        if y < SANDBOX_HEIGHT - 1 {
+            visit(x, y + 1);
+        }
+    }
+    points
+}
@@ -1,15 +1,40 @@
-use crate::particle::ParticleType;
-use crate::sandbox::Sandbox;
-use imgui::{Condition, Context, FontSource, Slider, StyleColor, StyleVar, Window as ImWindow};
-use imgui_wgpu::{Renderer, RendererConfig};
+use crate::brush::{BrushShape, MAX_BRUSH_SIZE, MAX_BRUSH_TEMPERATURE, MIN_BRUSH_TEMPERATURE};
+use crate::game::Game;
+use crate::particle::{ParticleCategory, ParticleType};
+use crate::prefab::{delete_prefab, load_prefabs, save_prefab, Prefab};
+use crate::recording::{
+    RecordingFormat, MAX_RECORDING_FRAME_SKIP, MAX_RECORDING_SCALE, MIN_GIF_FRAME_SKIP,
+};
+use crate::sandbox::{
+    heat_map_color, particle_color, Sandbox, ViewMode, HEAT_MAP_GRADIENT, SANDBOX_HEIGHT,
+    SANDBOX_WIDTH,
+};
+use crate::screenshot::MAX_SCREENSHOT_SCALE;
+use crate::settings::{settings_directory, Settings};
+use crate::statistics::HISTORY_LENGTH;
+use crate::symmetry::{SymmetryMode, MAX_RADIAL_FOLDS};
+use crate::tool::Tool;
+use imgui::{
+    Condition, Context, FontSource, Image, MouseButton, PlotLines, Slider, StyleColor, StyleVar,
+    TextureId, Window as ImWindow,
+};
+use imgui_wgpu::{Renderer, RendererConfig, Texture, TextureConfig};
 use imgui_winit_support::{HiDpiMode, WinitPlatform};
 use pixels::wgpu::*;
 use puffin::GlobalProfiler;
 use puffin_imgui::ProfilerUi;
+use std::borrow::Cow;
 use std::time::Duration;
 use winit::event::Event;
 use winit::window::Window;
 
+/// The width of the particle palette, which scrolls horizontally when the window is narrower
+const PALETTE_WIDTH: f32 = 2640.0;
+/// How many cells wide and tall each pixel of the minimap is
+const MINIMAP_SCALE: usize = 2;
+/// How long the result of taking a screenshot is shown for
+const SCREENSHOT_STATUS_DURATION: Duration = Duration::from_secs(4);
+
 pub struct UI {
     imgui: Context,
     imgui_platform: WinitPlatform,
@@ -18,9 +43,22 @@ pub struct UI {
     should_display_ui: bool,
     should_display_fps: bool,
     should_display_profiler: bool,
+    should_display_prefabs: bool,
+    should_display_inspector: bool,
+    should_display_statistics: bool,
+    should_display_minimap: bool,
+    should_display_recording: bool,
 
     was_paused_before_popup: bool,
     profiler_ui: ProfilerUi,
+
+    prefabs: Vec<(Prefab, TextureId)>,
+    prefab_name: String,
+    prefab_status: Option<String>,
+
+    keymap_problems: Vec<String>,
+
+    minimap_texture: TextureId,
 }
 
 impl UI {
@@ -34,8 +72,8 @@ impl UI {
             size_pixels: (16.0 * imgui_platform.hidpi_factor()) as f32,
             config: None,
         }]);
-        imgui.set_ini_filename(None);
-        let imgui_renderer = Renderer::new(
+        imgui.set_ini_filename(settings_directory().map(|directory| directory.join("imgui.ini")));
+        let mut imgui_renderer = Renderer::new(
             &mut imgui,
             device,
             queue,
@@ -45,6 +83,25 @@ impl UI {
             },
         );
 
+        let mut prefabs = Vec::new();
+        reload_prefabs(&mut prefabs, &mut imgui_renderer, device, queue);
+
+        let minimap_texture = Texture::new(
+            device,
+            &imgui_renderer,
+            TextureConfig {
+                size: Extent3d {
+                    width: (SANDBOX_WIDTH / MINIMAP_SCALE) as u32,
+                    height: (SANDBOX_HEIGHT / MINIMAP_SCALE) as u32,
+                    depth_or_array_layers: 1,
+                },
+                label: Some("minimap_texture"),
+                format: Some(TextureFormat::Rgba8UnormSrgb),
+                ..Default::default()
+            },
+        );
+        let minimap_texture = imgui_renderer.textures.insert(minimap_texture);
+
         Self {
             imgui,
             imgui_platform,
@@ -53,9 +110,22 @@ impl UI {
             should_display_ui: true,
             should_display_fps: cfg!(debug_assertions),
             should_display_profiler: false,
+            should_display_prefabs: false,
+            should_display_inspector: false,
+            should_display_statistics: false,
+            should_display_minimap: false,
+            should_display_recording: false,
 
             was_paused_before_popup: false,
             profiler_ui: ProfilerUi::default(),
+
+            prefabs,
+            prefab_name: String::new(),
+            prefab_status: None,
+
+            keymap_problems: Vec::new(),
+
+            minimap_texture,
         }
     }
 
@@ -71,6 +141,26 @@ impl UI {
             .reload_font_texture(&mut self.imgui, device, queue);
     }
 
+    pub fn load_settings(&mut self, settings: &Settings) {
+        self.should_display_ui = settings.display_ui;
+        self.should_display_fps = settings.display_fps;
+        self.should_display_prefabs = settings.display_prefabs;
+        self.should_display_inspector = settings.display_inspector;
+        self.should_display_statistics = settings.display_statistics;
+        self.should_display_minimap = settings.display_minimap;
+        self.should_display_recording = settings.display_recording;
+    }
+
+    pub fn save_settings(&self, settings: &mut Settings) {
+        settings.display_ui = self.should_display_ui;
+        settings.display_fps = self.should_display_fps;
+        settings.display_prefabs = self.should_display_prefabs;
+        settings.display_inspector = self.should_display_inspector;
+        settings.display_statistics = self.should_display_statistics;
+        settings.display_minimap = self.should_display_minimap;
+        settings.display_recording = self.should_display_recording;
+    }
+
     pub fn toggle_display_ui(&mut self) {
         self.should_display_ui = !self.should_display_ui;
     }
@@ -84,6 +174,30 @@ impl UI {
         puffin::set_scopes_on(self.should_display_profiler);
     }
 
+    pub fn toggle_display_prefabs(&mut self) {
+        self.should_display_prefabs = !self.should_display_prefabs;
+    }
+
+    pub fn toggle_display_inspector(&mut self) {
+        self.should_display_inspector = !self.should_display_inspector;
+    }
+
+    pub fn toggle_display_statistics(&mut self) {
+        self.should_display_statistics = !self.should_display_statistics;
+    }
+
+    pub fn toggle_display_minimap(&mut self) {
+        self.should_display_minimap = !self.should_display_minimap;
+    }
+
+    pub fn toggle_display_recording(&mut self) {
+        self.should_display_recording = !self.should_display_recording;
+    }
+
+    pub fn show_keymap_problems(&mut self, problems: Vec<String>) {
+        self.keymap_problems = problems;
+    }
+
     pub fn start_of_frame(&mut self, time_since_last_frame: Duration) {
         if self.should_display_profiler {
             GlobalProfiler::lock().new_frame();
@@ -100,10 +214,7 @@ impl UI {
 
     pub fn render(
         &mut self,
-        sandbox: &mut Sandbox,
-        selected_particle: &mut Option<ParticleType>,
-        brush_size: &mut u8,
-        game_paused: &mut bool,
+        game: &mut Game,
 
         window: &Window,
         device: &Device,
@@ -111,59 +222,90 @@ impl UI {
         encoder: &mut CommandEncoder,
         render_texture: &TextureView,
     ) {
+        let Game {
+            sandbox,
+            selected_particle,
+            tool,
+            brush_size,
+            brush_shape,
+            spray_density,
+            brush_filter,
+            temperature_rate,
+            target_temperature,
+            wind_strength,
+            symmetry,
+            autosave_world,
+            screenshot_scale,
+            view_mode,
+            stamp,
+            is_pasting,
+            is_paused: game_paused,
+            ..
+        } = game;
+        let mut should_empty_sandbox = false;
         let ui = self.imgui.frame();
 
         // Function to create particle selection buttons
         let mut button_x = 0.0;
-        let mut particle_selector_button = |text: &str, ptype: Option<ParticleType>| {
-            ui.set_cursor_pos([
-                button_x,
-                if ptype == *selected_particle {
-                    0.0
+        let mut particle_selector_button =
+            |ptype: Option<ParticleType>, starts_category: Option<ParticleCategory>| {
+                // Leave a gap before each category, labelled with its name
+                if let Some(category) = starts_category {
+                    button_x += 16.0;
+                    ui.set_cursor_pos([button_x, 0.0]);
+                    ui.text(category.name());
+                }
+
+                ui.set_cursor_pos([
+                    button_x,
+                    if ptype == *selected_particle {
+                        18.0
+                    } else {
+                        26.0
+                    },
+                ]);
+                button_x += if ptype == *selected_particle {
+                    108.0
                 } else {
-                    8.0
-                },
-            ]);
-            button_x += if ptype == *selected_particle {
-                108.0
-            } else {
-                93.0
-            };
+                    93.0
+                };
 
-            let button_color = ptype_ui_color(ptype);
-            let button_color = [
-                button_color[0] as f32 / 255.0,
-                button_color[1] as f32 / 255.0,
-                button_color[2] as f32 / 255.0,
-                0.95,
-            ];
-            let text_color = ptype_ui_text_color(ptype);
-            let text_color = [
-                text_color[0] as f32 / 255.0,
-                text_color[1] as f32 / 255.0,
-                text_color[2] as f32 / 255.0,
-                1.0,
-            ];
-            let style1 = [
-                ui.push_style_color(StyleColor::Button, button_color),
-                ui.push_style_color(StyleColor::ButtonHovered, button_color),
-                ui.push_style_color(StyleColor::ButtonActive, button_color),
-                ui.push_style_color(StyleColor::Text, text_color),
-            ];
-            let style2 = ui.push_style_var(StyleVar::FrameRounding(6.0));
-            let size = if ptype == *selected_particle {
-                [100.0, 55.0]
-            } else {
-                [85.0, 40.0]
+                let button_color = ptype_ui_color(ptype);
+                let button_color = [
+                    button_color[0] as f32 / 255.0,
+                    button_color[1] as f32 / 255.0,
+                    button_color[2] as f32 / 255.0,
+                    0.95,
+                ];
+                let text_color = ptype_ui_text_color(ptype);
+                let text_color = [
+                    text_color[0] as f32 / 255.0,
+                    text_color[1] as f32 / 255.0,
+                    text_color[2] as f32 / 255.0,
+                    1.0,
+                ];
+                let style1 = [
+                    ui.push_style_color(StyleColor::Button, button_color),
+                    ui.push_style_color(StyleColor::ButtonHovered, button_color),
+                    ui.push_style_color(StyleColor::ButtonActive, button_color),
+                    ui.push_style_color(StyleColor::Text, text_color),
+                ];
+                let style2 = ui.push_style_var(StyleVar::FrameRounding(6.0));
+                let size = if ptype == *selected_particle {
+                    [100.0, 55.0]
+                } else {
+                    [85.0, 40.0]
+                };
+                let text = ptype.map(|ptype| ptype.name()).unwrap_or("Delete Tool");
+                if ui.button_with_size(text, size) {
+                    *selected_particle = ptype;
+                    *is_pasting = false;
+                }
+                for style in style1 {
+                    style.pop();
+                }
+                style2.pop();
             };
-            if ui.button_with_size(text, size) {
-                *selected_particle = ptype;
-            }
-            for style in style1 {
-                style.pop();
-            }
-            style2.pop();
-        };
 
         // Setup styles
         let foreground_color1 = [
@@ -228,7 +370,7 @@ impl UI {
             // Draw particle selection buttons
             ImWindow::new("particle_selection_window")
                 .always_auto_resize(true)
-                .content_size([1416.0, 55.0])
+                .content_size([PALETTE_WIDTH, 73.0])
                 .position([108.0, 10.0], Condition::Always)
                 .title_bar(false)
                 .draw_background(false)
@@ -236,26 +378,21 @@ impl UI {
                 .resizable(false)
                 .horizontal_scrollbar(true)
                 .build(&ui, || {
-                    particle_selector_button("Delete Tool", None);
-                    particle_selector_button("Sand", Some(ParticleType::Sand));
-                    particle_selector_button("Water", Some(ParticleType::Water));
-                    particle_selector_button("Acid", Some(ParticleType::Acid));
-                    particle_selector_button("Iridium", Some(ParticleType::Iridium));
-                    particle_selector_button("Replicator", Some(ParticleType::Replicator));
-                    particle_selector_button("Plant", Some(ParticleType::Plant));
-                    particle_selector_button("Cryotheum", Some(ParticleType::Cryotheum));
-                    particle_selector_button("Unstable", Some(ParticleType::Unstable));
-                    particle_selector_button("Electricity", Some(ParticleType::Electricity));
-                    particle_selector_button("Life", Some(ParticleType::Life));
-                    particle_selector_button("Fire", Some(ParticleType::Fire));
-                    particle_selector_button("Mirror", Some(ParticleType::Mirror));
-                    particle_selector_button("Glitch", Some(ParticleType::Glitch));
+                    particle_selector_button(None, None);
+                    for category in ParticleCategory::ALL {
+                        let mut starts_category = Some(category);
+                        for ptype in ParticleType::ALL {
+                            if ptype.category() == category {
+                                particle_selector_button(Some(ptype), starts_category.take());
+                            }
+                        }
+                    }
                 });
 
-            let y = if window.inner_size().width < 1416 {
-                87.0
+            let y = if (window.inner_size().width as f32) < PALETTE_WIDTH {
+                105.0
             } else {
-                75.0
+                93.0
             };
             let was_paused_before_popup = &mut self.was_paused_before_popup;
             ImWindow::new("second_row_window")
@@ -285,7 +422,7 @@ impl UI {
                         .build(&ui, || {
                             ui.text("Empty Sandbox?");
                             if ui.button_with_size("Yes", [60.0, 30.0]) {
-                                sandbox.empty_out();
+                                should_empty_sandbox = true;
                                 ui.close_current_popup();
                                 *game_paused = *was_paused_before_popup;
                             }
@@ -302,7 +439,133 @@ impl UI {
                     ];
                     // Draw the brush size slider
                     ui.set_cursor_pos([219.0, 4.0]);
-                    Slider::new("Brush Size", 1, 10).build(&ui, brush_size);
+                    Slider::new("Brush Size", 1, MAX_BRUSH_SIZE).build(&ui, brush_size);
+                    // Draw the view mode selector
+                    ui.same_line();
+                    let mut view_mode_i =
+                        ViewMode::ALL.iter().position(|v| v == view_mode).unwrap();
+                    ui.set_next_item_width(150.0);
+                    if ui.combo("View", &mut view_mode_i, &ViewMode::ALL, |mode| {
+                        Cow::Borrowed(mode.name())
+                    }) {
+                        *view_mode = ViewMode::ALL[view_mode_i];
+                    }
+                    // Draw the tool selector
+                    let previous_tool = *tool;
+                    ui.set_cursor_pos([0.0, 36.0]);
+                    ui.radio_button("Freehand", tool, Tool::Freehand);
+                    ui.same_line();
+                    ui.radio_button("Line", tool, Tool::Line);
+                    ui.same_line();
+                    ui.radio_button("Rectangle", tool, Tool::Rectangle);
+                    ui.same_line();
+                    ui.radio_button("Filled Rectangle", tool, Tool::FilledRectangle);
+                    ui.same_line();
+                    ui.radio_button("Ellipse", tool, Tool::Ellipse);
+                    ui.same_line();
+                    ui.radio_button("Filled Ellipse", tool, Tool::FilledEllipse);
+                    ui.same_line();
+                    ui.radio_button("Fill", tool, Tool::Fill);
+                    ui.same_line();
+                    ui.radio_button("Heat", tool, Tool::Heat);
+                    ui.same_line();
+                    ui.radio_button("Cool", tool, Tool::Cool);
+                    ui.same_line();
+                    ui.radio_button("Set Temperature", tool, Tool::SetTemperature);
+                    ui.same_line();
+                    ui.radio_button("Select", tool, Tool::Select);
+                    ui.same_line();
+                    ui.radio_button("Drag", tool, Tool::Drag);
+                    ui.same_line();
+                    ui.radio_button("Wind", tool, Tool::Wind);
+                    if *tool != previous_tool {
+                        *is_pasting = false;
+                    }
+                    // Draw the temperature sliders for the temperature tools
+                    if *tool == Tool::Heat || *tool == Tool::Cool {
+                        ui.same_line();
+                        ui.set_next_item_width(150.0);
+                        Slider::new("Rate", 1, 50).build(&ui, temperature_rate);
+                    }
+                    if *tool == Tool::Wind {
+                        ui.same_line();
+                        ui.set_next_item_width(150.0);
+                        Slider::new("Wind Strength", 0.05, 1.0).build(&ui, wind_strength);
+                    }
+                    if *tool == Tool::SetTemperature {
+                        ui.same_line();
+                        ui.set_next_item_width(150.0);
+                        Slider::new("Temperature", MIN_BRUSH_TEMPERATURE, MAX_BRUSH_TEMPERATURE)
+                            .build(&ui, target_temperature);
+                    }
+                    // Draw the brush shape selector
+                    ui.set_cursor_pos([0.0, 68.0]);
+                    ui.radio_button("Square", brush_shape, BrushShape::Square);
+                    ui.same_line();
+                    ui.radio_button("Circle", brush_shape, BrushShape::Circle);
+                    ui.same_line();
+                    ui.radio_button("Spray", brush_shape, BrushShape::Spray);
+                    if stamp.is_some() {
+                        ui.same_line();
+                        ui.radio_button("Stamp", brush_shape, BrushShape::Stamp);
+                    }
+                    // Draw the spray density slider when spraying
+                    if *brush_shape == BrushShape::Spray {
+                        ui.same_line();
+                        Slider::new("Spray Density", 0.01, 1.0).build(&ui, spray_density);
+                    }
+                    // Draw the brush filter selector
+                    ui.same_line();
+                    let filters: Vec<Option<ParticleType>> = std::iter::once(None)
+                        .chain(ParticleType::ALL.iter().copied().map(Some))
+                        .collect();
+                    let mut filter_i = filters.iter().position(|f| f == brush_filter).unwrap();
+                    ui.set_next_item_width(150.0);
+                    if ui.combo(
+                        "Only Affect",
+                        &mut filter_i,
+                        &filters,
+                        |filter| match filter {
+                            Some(ptype) => Cow::Borrowed(ptype.name()),
+                            None if selected_particle.is_some() => Cow::Borrowed("Empty Cells"),
+                            None => Cow::Borrowed("All Particles"),
+                        },
+                    ) {
+                        *brush_filter = filters[filter_i];
+                    }
+                    // Draw the symmetry selector
+                    ui.set_cursor_pos([0.0, 100.0]);
+                    ui.text("Symmetry:");
+                    ui.same_line();
+                    ui.radio_button("Off", &mut symmetry.mode, SymmetryMode::Off);
+                    ui.same_line();
+                    ui.radio_button("Vertical", &mut symmetry.mode, SymmetryMode::Vertical);
+                    ui.same_line();
+                    ui.radio_button("Horizontal", &mut symmetry.mode, SymmetryMode::Horizontal);
+                    ui.same_line();
+                    ui.radio_button("Both", &mut symmetry.mode, SymmetryMode::Both);
+                    ui.same_line();
+                    ui.radio_button("Radial", &mut symmetry.mode, SymmetryMode::Radial);
+                    if symmetry.mode == SymmetryMode::Radial {
+                        ui.same_line();
+                        ui.set_next_item_width(150.0);
+                        Slider::new("Folds", 2, MAX_RADIAL_FOLDS)
+                            .build(&ui, &mut symmetry.radial_folds);
+                    }
+                    // Draw the autosave checkbox
+                    ui.same_line();
+                    ui.checkbox("Restore World on Launch", autosave_world);
+                    // Draw the screenshot scale slider
+                    ui.same_line();
+                    ui.set_next_item_width(100.0);
+                    Slider::new("Screenshot Scale", 1, MAX_SCREENSHOT_SCALE)
+                        .build(&ui, screenshot_scale);
+                    // Draw the infection chance slider when Virus is selected
+                    if *selected_particle == Some(ParticleType::Virus) {
+                        ui.set_cursor_pos([0.0, 132.0]);
+                        Slider::new("Infection Chance", 0.0, 1.0)
+                            .build(&ui, &mut sandbox.virus_infection_chance);
+                    }
                 });
         }
 
@@ -321,6 +584,23 @@ impl UI {
                 .build(&ui, || ui.text(format!("FPS: {:.0}", ui.io().framerate)));
         }
 
+        // Draw where the last screenshot was saved for a few seconds, above the FPS counter
+        if let Some((screenshot_status, time)) = &game.screenshot_status {
+            if time.elapsed() < SCREENSHOT_STATUS_DURATION {
+                let height: f32 = window.inner_size().to_logical(window.scale_factor()).height;
+                let y = height - if self.should_display_fps { 52.0 } else { 26.0 };
+                ImWindow::new("screenshot_status_window")
+                    .always_auto_resize(true)
+                    .position([10.0, y], Condition::Always)
+                    .title_bar(false)
+                    .draw_background(false)
+                    .movable(false)
+                    .resizable(false)
+                    .no_inputs()
+                    .build(&ui, || ui.text(screenshot_status));
+            }
+        }
+
         for style in style1 {
             style.pop();
         }
@@ -330,6 +610,320 @@ impl UI {
             self.profiler_ui.window(&ui);
         }
 
+        if should_empty_sandbox {
+            game.empty_sandbox();
+        }
+
+        // Draw the heat map legend
+        if game.view_mode == ViewMode::Heat {
+            let size = window.inner_size().to_logical::<f32>(window.scale_factor());
+            ImWindow::new("heat_map_legend_window")
+                .always_auto_resize(true)
+                .position([size.width - 10.0, size.height - 10.0], Condition::Always)
+                .position_pivot([1.0, 1.0])
+                .title_bar(false)
+                .movable(false)
+                .resizable(false)
+                .no_inputs()
+                .build(&ui, || {
+                    ui.text("Temperature");
+                    let (min_temperature, _) = HEAT_MAP_GRADIENT[0];
+                    let (max_temperature, _) = HEAT_MAP_GRADIENT[HEAT_MAP_GRADIENT.len() - 1];
+                    let bar_width = 240.0;
+                    let [x, y] = ui.cursor_screen_pos();
+                    let draw_list = ui.get_window_draw_list();
+                    let to_color = |temperature: i16| {
+                        let (r, g, b) = heat_map_color(temperature);
+                        [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+                    };
+                    let to_x = |temperature: i16| {
+                        x + bar_width * (temperature - min_temperature) as f32
+                            / (max_temperature - min_temperature) as f32
+                    };
+                    for stops in HEAT_MAP_GRADIENT.windows(2) {
+                        let ((t1, _), (t2, _)) = (stops[0], stops[1]);
+                        draw_list.add_rect_filled_multicolor(
+                            [to_x(t1), y],
+                            [to_x(t2), y + 16.0],
+                            to_color(t1),
+                            to_color(t2),
+                            to_color(t2),
+                            to_color(t1),
+                        );
+                    }
+                    let [start_x, _] = ui.cursor_pos();
+                    ui.dummy([bar_width, 16.0]);
+                    let [_, text_y] = ui.cursor_pos();
+                    for (temperature, _) in HEAT_MAP_GRADIENT {
+                        let text = temperature.to_string();
+                        let text_width = ui.calc_text_size(&text)[0];
+                        let text_x = (to_x(temperature) - x - text_width / 2.0)
+                            .clamp(0.0, bar_width - text_width);
+                        ui.set_cursor_pos([start_x + text_x, text_y]);
+                        ui.text(text);
+                    }
+                });
+        }
+
+        // Draw the particle inspector under the cursor
+        if self.should_display_inspector && !ui.io().want_capture_mouse {
+            let (x, y) = game.cursor_grid_position;
+            ui.tooltip(|| {
+                ui.text(format!("X: {}, Y: {}", x, y));
+                match game.sandbox[x][y] {
+                    Some(particle) => {
+                        ui.text(particle.ptype.name());
+                        ui.text(format!("Temperature: {}", particle.temperature));
+                        for (label, value) in particle.extra_data_description() {
+                            ui.text(format!("{}: {}", label, value));
+                        }
+                    }
+                    None => ui.text("Empty"),
+                }
+            });
+        }
+
+        // Draw the statistics
+        if self.should_display_statistics {
+            let statistics = &game.sandbox.statistics;
+            let current = &statistics.current;
+            let selected_particle = game.selected_particle;
+            ImWindow::new("Statistics")
+                .size([340.0, 520.0], Condition::FirstUseEver)
+                .opened(&mut self.should_display_statistics)
+                .build(&ui, || {
+                    ui.text(format!("Total Particles: {}", current.total));
+                    ui.text(format!(
+                        "Average Temperature: {:.1}",
+                        current.average_temperature
+                    ));
+                    ui.text(format!("Max Temperature: {}", current.max_temperature));
+                    ui.separator();
+
+                    // Graph the last few minutes, one point per second
+                    let graph = |label: &str, values: Vec<f32>, overlay_text: String| {
+                        PlotLines::new(&ui, label, &values)
+                            .overlay_text(overlay_text)
+                            .graph_size([240.0, 50.0])
+                            .build();
+                    };
+                    let minutes = HISTORY_LENGTH as f32 / 60.0;
+                    ui.text(format!("Last {} minutes:", minutes));
+                    graph(
+                        "Total",
+                        statistics
+                            .history
+                            .iter()
+                            .map(|sample| sample.total as f32)
+                            .collect(),
+                        current.total.to_string(),
+                    );
+                    graph(
+                        "Temperature",
+                        statistics
+                            .history
+                            .iter()
+                            .map(|sample| sample.average_temperature)
+                            .collect(),
+                        format!("{:.1}", current.average_temperature),
+                    );
+                    if let Some(ptype) = selected_particle {
+                        graph(
+                            ptype.name(),
+                            statistics
+                                .history
+                                .iter()
+                                .map(|sample| sample.counts[ptype as usize] as f32)
+                                .collect(),
+                            current.counts[ptype as usize].to_string(),
+                        );
+                    }
+                    ui.separator();
+
+                    // List the count of every type in the sandbox
+                    for ptype in ParticleType::ALL {
+                        let count = current.counts[ptype as usize];
+                        if count > 0 {
+                            ui.text(format!("{}: {}", ptype.name(), count));
+                        }
+                    }
+                });
+        }
+
+        // Draw the minimap, with the area the camera is showing outlined
+        if self.should_display_minimap {
+            let (width, height) = (
+                SANDBOX_WIDTH / MINIMAP_SCALE,
+                SANDBOX_HEIGHT / MINIMAP_SCALE,
+            );
+            if let Some(texture) = self.imgui_renderer.textures.get(self.minimap_texture) {
+                texture.write(
+                    queue,
+                    &render_minimap(&game.sandbox),
+                    width as u32,
+                    height as u32,
+                );
+            }
+            let minimap_texture = self.minimap_texture;
+            ImWindow::new("Minimap")
+                .always_auto_resize(true)
+                .resizable(false)
+                .position([10.0, 300.0], Condition::FirstUseEver)
+                .opened(&mut self.should_display_minimap)
+                .build(&ui, || {
+                    let [image_x, image_y] = ui.cursor_screen_pos();
+                    Image::new(minimap_texture, [width as f32, height as f32]).build(&ui);
+
+                    // Click or drag to center the camera on a point
+                    if ui.is_item_hovered() && ui.is_mouse_down(MouseButton::Left) {
+                        let [mouse_x, mouse_y] = ui.io().mouse_pos;
+                        let x = ((mouse_x - image_x).max(0.0) as usize * MINIMAP_SCALE)
+                            .min(SANDBOX_WIDTH - 1);
+                        let y = ((mouse_y - image_y).max(0.0) as usize * MINIMAP_SCALE)
+                            .min(SANDBOX_HEIGHT - 1);
+                        game.camera
+                            .move_cell_to((x, y), (SANDBOX_WIDTH / 2, SANDBOX_HEIGHT / 2));
+                    }
+
+                    let (x, y, visible_width, visible_height) = game.camera.visible_region();
+                    let scale = MINIMAP_SCALE as f32;
+                    ui.get_window_draw_list()
+                        .add_rect(
+                            [image_x + x as f32 / scale, image_y + y as f32 / scale],
+                            [
+                                image_x + (x + visible_width) as f32 / scale,
+                                image_y + (y + visible_height) as f32 / scale,
+                            ],
+                            [1.0, 1.0, 1.0, 1.0],
+                        )
+                        .build();
+                });
+        }
+
+        // Draw the recording controls
+        if self.should_display_recording {
+            ImWindow::new("Recording")
+                .always_auto_resize(true)
+                .opened(&mut self.should_display_recording)
+                .build(&ui, || {
+                    if let Some(recording) = &game.recording {
+                        ui.text(format!("Recording: {} frames", recording.frames_recorded));
+                        if ui.button("Stop Recording") {
+                            game.toggle_recording();
+                        }
+                        return;
+                    }
+
+                    let mut format_i = RecordingFormat::ALL
+                        .iter()
+                        .position(|f| *f == game.recording_format)
+                        .unwrap();
+                    ui.set_next_item_width(150.0);
+                    if ui.combo("Format", &mut format_i, &RecordingFormat::ALL, |format| {
+                        Cow::Borrowed(format.name())
+                    }) {
+                        game.recording_format = RecordingFormat::ALL[format_i];
+                    }
+                    ui.set_next_item_width(150.0);
+                    let min_frame_skip = match game.recording_format {
+                        RecordingFormat::Gif => MIN_GIF_FRAME_SKIP,
+                        RecordingFormat::PngSequence => 0,
+                    };
+                    game.recording_frame_skip = game.recording_frame_skip.max(min_frame_skip);
+                    Slider::new("Frame Skip", min_frame_skip, MAX_RECORDING_FRAME_SKIP)
+                        .build(&ui, &mut game.recording_frame_skip);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Updates to skip between recorded frames");
+                    }
+                    ui.set_next_item_width(150.0);
+                    Slider::new("Scale", 1, MAX_RECORDING_SCALE)
+                        .build(&ui, &mut game.recording_scale);
+                    if ui.button("Start Recording") {
+                        game.toggle_recording();
+                    }
+                    if let Some(recording_status) = &game.recording_status {
+                        ui.text(recording_status);
+                    }
+                });
+        }
+
+        // Draw the keymap problems
+        if !self.keymap_problems.is_empty() {
+            let mut opened = true;
+            ImWindow::new("Keymap Problems")
+                .always_auto_resize(true)
+                .opened(&mut opened)
+                .build(&ui, || {
+                    for problem in &self.keymap_problems {
+                        ui.text(problem);
+                    }
+                });
+            if !opened {
+                self.keymap_problems.clear();
+            }
+        }
+
+        // Draw the prefab library
+        if self.should_display_prefabs {
+            let prefabs = &self.prefabs;
+            let prefab_name = &mut self.prefab_name;
+            let prefab_status = &mut self.prefab_status;
+            let mut should_reload_prefabs = false;
+            ImWindow::new("Prefabs")
+                .size([320.0, 420.0], Condition::FirstUseEver)
+                .opened(&mut self.should_display_prefabs)
+                .build(&ui, || {
+                    // Save the current stamp as a new prefab
+                    ui.input_text("Name", prefab_name).build();
+                    if ui.button("Save Stamp as Prefab") {
+                        *prefab_status = match &game.stamp {
+                            Some(stamp) => match save_prefab(prefab_name, stamp) {
+                                Ok(()) => {
+                                    should_reload_prefabs = true;
+                                    None
+                                }
+                                Err(error) => Some(format!("Failed to save: {}", error)),
+                            },
+                            None => Some("Copy a region or capture a stamp first".to_owned()),
+                        };
+                    }
+                    if let Some(prefab_status) = prefab_status {
+                        ui.text_wrapped(prefab_status);
+                    }
+                    ui.separator();
+
+                    // List the saved prefabs
+                    for (prefab, texture_id) in prefabs {
+                        let scale = 96.0 / prefab.stamp.width.max(prefab.stamp.height) as f32;
+                        Image::new(
+                            *texture_id,
+                            [
+                                prefab.stamp.width as f32 * scale,
+                                prefab.stamp.height as f32 * scale,
+                            ],
+                        )
+                        .build(&ui);
+                        ui.same_line();
+                        ui.group(|| {
+                            ui.text(&prefab.name);
+                            if ui.button(format!("Place##{}", prefab.name)) {
+                                game.stamp = Some(prefab.stamp.clone());
+                                game.paste();
+                            }
+                            if ui.button(format!("Delete##{}", prefab.name)) {
+                                if let Err(error) = delete_prefab(&prefab.name) {
+                                    *prefab_status = Some(format!("Failed to delete: {}", error));
+                                }
+                                should_reload_prefabs = true;
+                            }
+                        });
+                    }
+                });
+            if should_reload_prefabs {
+                reload_prefabs(&mut self.prefabs, &mut self.imgui_renderer, device, queue);
+            }
+        }
+
         // Render
         self.imgui_platform.prepare_render(&ui, window);
         let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
@@ -359,27 +953,61 @@ impl UI {
     }
 }
 
+/// Returns the sandbox scaled down by MINIMAP_SCALE, as RGBA pixels
+fn render_minimap(sandbox: &Sandbox) -> Vec<u8> {
+    let mut pixels =
+        Vec::with_capacity((SANDBOX_WIDTH / MINIMAP_SCALE) * (SANDBOX_HEIGHT / MINIMAP_SCALE) * 4);
+    for y in (0..SANDBOX_HEIGHT).step_by(MINIMAP_SCALE) {
+        for x in (0..SANDBOX_WIDTH).step_by(MINIMAP_SCALE) {
+            let (r, g, b) = match &sandbox[x][y] {
+                Some(particle) => particle_color(particle, 0),
+                None => (30, 30, 30),
+            };
+            pixels.extend_from_slice(&[r, g, b, 255]);
+        }
+    }
+    pixels
+}
+
+/// Load the prefabs from disk, and upload their thumbnails, replacing any previously loaded prefabs
+fn reload_prefabs(
+    prefabs: &mut Vec<(Prefab, TextureId)>,
+    renderer: &mut Renderer,
+    device: &Device,
+    queue: &Queue,
+) {
+    for (_, texture_id) in prefabs.drain(..) {
+        renderer.textures.remove(texture_id);
+    }
+
+    for prefab in load_prefabs() {
+        let (width, height) = (prefab.stamp.width as u32, prefab.stamp.height as u32);
+        let texture = Texture::new(
+            device,
+            renderer,
+            TextureConfig {
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                label: Some("prefab_thumbnail_texture"),
+                format: Some(TextureFormat::Rgba8UnormSrgb),
+                ..Default::default()
+            },
+        );
+        texture.write(queue, &prefab.stamp.render(), width, height);
+        prefabs.push((prefab, renderer.textures.insert(texture)));
+    }
+}
+
 pub fn ptype_ui_color(ptype: Option<ParticleType>) -> [u8; 3] {
     match ptype {
         None => [26, 26, 26],
-        Some(ParticleType::Sand) => [196, 192, 135],
-        Some(ParticleType::Water) => [26, 91, 165],
-        Some(ParticleType::Acid) => [148, 219, 10],
-        Some(ParticleType::Iridium) => [100, 100, 100],
-        Some(ParticleType::Replicator) => [78, 21, 77],
-        Some(ParticleType::Plant) => [6, 89, 9],
-        Some(ParticleType::Cryotheum) => [12, 193, 255],
-        Some(ParticleType::Unstable) => [94, 78, 55],
-        Some(ParticleType::Electricity) => [255, 244, 49],
-        Some(ParticleType::Glass) => unreachable!(),
-        Some(ParticleType::Life) => [135, 12, 211],
-        Some(ParticleType::SuperLife) => unreachable!(),
-        Some(ParticleType::Blood) => unreachable!(),
-        Some(ParticleType::Smoke) => unreachable!(),
-        Some(ParticleType::Fire) => [255, 151, 20],
-        Some(ParticleType::Mirror) => [78, 216, 131],
-        Some(ParticleType::Steam) => unreachable!(),
-        Some(ParticleType::Glitch) => [89, 96, 90],
+        Some(ptype) => {
+            let (r, g, b) = ptype.color();
+            [r, g, b]
+        }
     }
 }
 
@@ -395,15 +1023,23 @@ pub fn ptype_ui_text_color(ptype: Option<ParticleType>) -> [u8; 3] {
         Some(ParticleType::Cryotheum) => false,
         Some(ParticleType::Unstable) => true,
         Some(ParticleType::Electricity) => false,
-        Some(ParticleType::Glass) => unreachable!(),
+        Some(ParticleType::Glass) => false,
         Some(ParticleType::Life) => true,
-        Some(ParticleType::SuperLife) => unreachable!(),
-        Some(ParticleType::Blood) => unreachable!(),
-        Some(ParticleType::Smoke) => unreachable!(),
+        Some(ParticleType::SuperLife) => true,
+        Some(ParticleType::Blood) => true,
+        Some(ParticleType::Smoke) => true,
         Some(ParticleType::Fire) => false,
         Some(ParticleType::Mirror) => false,
-        Some(ParticleType::Steam) => unreachable!(),
+        Some(ParticleType::Steam) => false,
         Some(ParticleType::Glitch) => true,
+        Some(ParticleType::Salt) => false,
+        Some(ParticleType::SaltWater) => true,
+        Some(ParticleType::Snow) => false,
+        Some(ParticleType::Cloud) => false,
+        Some(ParticleType::Virus) => false,
+        Some(ParticleType::Antidote) => false,
+        Some(ParticleType::Ant) => true,
+        Some(ParticleType::Seed) => true,
     };
     if light {
         [204, 204, 204]